@@ -28,23 +28,59 @@
 //! prefer exposing the underlying slice or vector instead.
 //! However, `ByteStr` and `ByteString` implement many traits, including derivable traits,
 //! which makes them suitable for use as a private member of a struct or enum.
+//!
+//! # Optional features
+//!
+//! - `text`: adds constructors that decode text from other encodings
+//!   (e.g. `ByteString::from_utf16()`).
+//! - `proptest`: implements `proptest::arbitrary::Arbitrary` for `ByteString`
+//!   and provides the `bytes_strategy()` function.
+//! - `bytes`: adds conversions between `ByteString` and `bytes::Bytes`/`bytes::BytesMut`.
+//! - `smallvec`: adds conversions between `ByteString` and `smallvec::SmallVec<[u8; N]>`.
+//! - `crc`: adds `ByteStr::crc32()`.
+//! - `rkyv`: implements `rkyv`'s `Archive`, `Serialize` and `Deserialize` for `ByteString`,
+//!   which is archived as `rkyv::vec::ArchivedVec<u8>`.
 
 #![warn(missing_docs)]
 
-use std::borrow::{Borrow, BorrowMut};
-use std::fmt::{Debug, Error, Formatter};
+#[cfg(feature = "bytes")]
+extern crate bytes;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "rkyv")]
+extern crate rkyv;
+#[cfg(feature = "smallvec")]
+extern crate smallvec;
+
+use std::borrow::{Borrow, BorrowMut, Cow};
+use std::cmp::Ordering;
+use std::collections::TryReserveError;
+use std::convert::TryFrom;
+use std::ffi::{CString, NulError};
+use std::fmt::{Debug, Display, Error, Formatter, LowerHex, UpperHex, Write};
+use std::io::{self, BufRead, Read};
 use std::iter::FromIterator;
 use std::mem;
-use std::ops::{Deref, DerefMut};
+use std::num::ParseIntError;
+use std::ops::{Add, Deref, DerefMut, Index, IndexMut, Range};
+use std::slice::SliceIndex;
+#[cfg(feature = "text")]
+use std::string::FromUtf16Error;
 
 /// Wraps a byte slice and provides a `Debug` implementation
 /// that outputs the slice using the Rust byte string syntax (e.g. `b"abc"`).
+///
+/// `ByteStr`, `Box<ByteStr>` and `ByteString` all hash their bytes
+/// exactly like the underlying `[u8]`,
+/// so a `HashMap` keyed by `Box<ByteStr>` or `ByteString`
+/// can be queried with a `&ByteStr`.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
 pub struct ByteStr(pub [u8]);
 
 /// Wraps a vector of bytes and provides a `Debug` implementation
 /// that outputs the slice using the Rust byte string syntax (e.g. `b"abc"`).
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ByteString(pub Vec<u8>);
 
 impl ByteStr {
@@ -57,6 +93,660 @@ impl ByteStr {
     pub fn new_mut(s: &mut [u8]) -> &mut ByteStr {
         unsafe { mem::transmute(s) }
     }
+
+    /// Returns an iterator over `size` bytes of the `ByteStr` at a time,
+    /// starting at the end.
+    ///
+    /// The last chunk yielded contains the remaining bytes
+    /// at the start of the `ByteStr` and may be shorter than `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn rchunks(&self, size: usize) -> RChunks<'_> {
+        RChunks(self.0.rchunks(size))
+    }
+
+    /// Copies the `ByteStr` into owned chunks of `size` bytes, starting at the beginning.
+    ///
+    /// The last chunk may be shorter than `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn into_owned_chunks(&self, size: usize) -> Vec<ByteString> {
+        self.0.chunks(size).map(|chunk| ByteString::new(chunk.to_vec())).collect()
+    }
+
+    /// Reads a big-endian `u16` starting at `offset`.
+    ///
+    /// Returns `None` if fewer than 2 bytes are available from `offset`.
+    pub fn read_u16_be(&self, offset: usize) -> Option<u16> {
+        self.read_array(offset).map(u16::from_be_bytes)
+    }
+
+    /// Reads a little-endian `u16` starting at `offset`.
+    ///
+    /// Returns `None` if fewer than 2 bytes are available from `offset`.
+    pub fn read_u16_le(&self, offset: usize) -> Option<u16> {
+        self.read_array(offset).map(u16::from_le_bytes)
+    }
+
+    /// Reads a big-endian `u32` starting at `offset`.
+    ///
+    /// Returns `None` if fewer than 4 bytes are available from `offset`.
+    pub fn read_u32_be(&self, offset: usize) -> Option<u32> {
+        self.read_array(offset).map(u32::from_be_bytes)
+    }
+
+    /// Reads a little-endian `u32` starting at `offset`.
+    ///
+    /// Returns `None` if fewer than 4 bytes are available from `offset`.
+    pub fn read_u32_le(&self, offset: usize) -> Option<u32> {
+        self.read_array(offset).map(u32::from_le_bytes)
+    }
+
+    /// Reads a big-endian `u64` starting at `offset`.
+    ///
+    /// Returns `None` if fewer than 8 bytes are available from `offset`.
+    pub fn read_u64_be(&self, offset: usize) -> Option<u64> {
+        self.read_array(offset).map(u64::from_be_bytes)
+    }
+
+    /// Reads a little-endian `u64` starting at `offset`.
+    ///
+    /// Returns `None` if fewer than 8 bytes are available from `offset`.
+    pub fn read_u64_le(&self, offset: usize) -> Option<u64> {
+        self.read_array(offset).map(u64::from_le_bytes)
+    }
+
+    fn read_array<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+        let end = offset.checked_add(N)?;
+        let mut array = [0; N];
+        array.copy_from_slice(self.0.get(offset..end)?);
+        Some(array)
+    }
+
+    /// Checks if all bytes in the `ByteStr` are within the ASCII range (0x00 to 0x7F).
+    pub fn is_ascii(&self) -> bool {
+        self.0.is_ascii()
+    }
+
+    /// Converts the `ByteStr` to a `String` suitable for embedding in HTML or XML.
+    ///
+    /// The bytes are first decoded as UTF-8,
+    /// replacing invalid sequences with U+FFFD REPLACEMENT CHARACTER;
+    /// then `<`, `>`, `&`, `"` and `'` are replaced with character references.
+    pub fn escape_html(&self) -> String {
+        let text = String::from_utf8_lossy(&self.0);
+        let mut escaped = String::with_capacity(text.len());
+        for ch in text.chars() {
+            match ch {
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '&' => escaped.push_str("&amp;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                _ => escaped.push(ch),
+            }
+        }
+
+        escaped
+    }
+
+    /// Divides the `ByteStr` into two at an index,
+    /// or returns `None` if `mid` is greater than the length.
+    ///
+    /// The first `ByteStr` contains the bytes in `[0, mid)`
+    /// and the second contains the bytes in `[mid, len)`.
+    pub fn split_at_checked(&self, mid: usize) -> Option<(&ByteStr, &ByteStr)> {
+        self.0.split_at_checked(mid).map(|(a, b)| (ByteStr::new(a), ByteStr::new(b)))
+    }
+
+    /// Appends the byte string literal form of the `ByteStr` (e.g. `b"abc"`) to `out`.
+    ///
+    /// The appended bytes are the same as the `Debug` output, encoded as ASCII.
+    pub fn escape_into(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b"b\"");
+        for &byte in self {
+            out.extend(std::ascii::escape_default(byte));
+        }
+
+        out.push(b'"');
+    }
+
+    /// Compares the bytes of the `ByteStr` lexicographically with `other`.
+    pub fn cmp_bytes(&self, other: &[u8]) -> Ordering {
+        self.0.cmp(other)
+    }
+
+    /// Copies the `ByteStr` into a new `ByteString`.
+    pub fn cloned(&self) -> ByteString {
+        ByteString::new(self.0.to_vec())
+    }
+
+    /// Returns the index of the first occurrence of `b` at or after `start`.
+    ///
+    /// Returns `None` if `b` doesn't occur there or if `start` is out of bounds.
+    pub fn find_byte_from(&self, b: u8, start: usize) -> Option<usize> {
+        let tail = self.0.get(start..)?;
+        tail.iter().position(|&x| x == b).map(|i| start + i)
+    }
+
+    /// Returns the index of the last occurrence of `b` before `end`.
+    ///
+    /// If `end` is greater than the length of the `ByteStr`,
+    /// the whole `ByteStr` is searched.
+    pub fn rfind_byte_before(&self, b: u8, end: usize) -> Option<usize> {
+        let end = std::cmp::min(end, self.0.len());
+        self.0[..end].iter().rposition(|&x| x == b)
+    }
+
+    /// Returns an iterator over the non-overlapping occurrences of `needle`
+    /// in the `ByteStr`, along with their starting index.
+    ///
+    /// Matches are found from left to right;
+    /// when occurrences overlap, only the first one is yielded.
+    /// An empty `needle` matches at every index, including the end.
+    pub fn match_indices<'a>(&'a self, needle: &'a [u8]) -> MatchIndices<'a> {
+        MatchIndices {
+            haystack: &self.0,
+            needle,
+            position: 0,
+        }
+    }
+
+    /// Returns an iterator over the non-overlapping occurrences of `needle`
+    /// in the `ByteStr`.
+    ///
+    /// See `match_indices` for details on how matches are found.
+    pub fn matches<'a>(&'a self, needle: &'a [u8]) -> Matches<'a> {
+        Matches(self.match_indices(needle))
+    }
+
+    /// Returns a wrapper whose `Debug` implementation outputs the `ByteStr`
+    /// using the Rust byte string syntax, but keeps valid UTF-8 readable.
+    ///
+    /// Printable non-ASCII characters are written as is;
+    /// bytes that are not part of a valid UTF-8 sequence
+    /// and non-ASCII control characters are escaped as `\xNN`.
+    /// ASCII bytes are escaped as in the `Debug` implementation of `ByteStr`.
+    pub fn debug_utf8_aware(&self) -> impl Debug + '_ {
+        DebugUtf8Aware(self)
+    }
+
+    /// Returns a wrapper whose `Debug` implementation outputs the `ByteStr`
+    /// like the `Debug` implementation of `ByteStr`, but with a newline inserted
+    /// in the escaped body whenever a line would exceed `width` columns.
+    ///
+    /// Escape sequences are never split across lines,
+    /// so an escape longer than `width` occupies a line on its own.
+    pub fn debug_wrapped(&self, width: usize) -> impl Debug + '_ {
+        DebugWrapped { bytes: self, width }
+    }
+
+    /// Returns an iterator over the `ByteStr` split into chunks
+    /// of valid UTF-8 followed by invalid bytes.
+    ///
+    /// This mirrors `[u8]::utf8_chunks`, but yields the invalid bytes as a `ByteStr`.
+    pub fn utf8_chunks(&self) -> Utf8Chunks<'_> {
+        Utf8Chunks(self.0.utf8_chunks())
+    }
+
+    /// Returns a new `ByteString` containing the bytes of the `ByteStr` in reverse order.
+    pub fn reversed(&self) -> ByteString {
+        self.0.iter().rev().cloned().collect()
+    }
+
+    /// Reverses the order of the bytes in the `ByteStr`, in place.
+    pub fn reverse(&mut self) {
+        self.0.reverse()
+    }
+
+    /// Returns an iterator over the non-empty runs of non-whitespace bytes
+    /// in the `ByteStr`, separated by any amount of ASCII whitespace.
+    ///
+    /// ASCII whitespace is defined as in `u8::is_ascii_whitespace`.
+    pub fn split_ascii_whitespace(&self) -> SplitAsciiWhitespace<'_> {
+        SplitAsciiWhitespace(&self.0)
+    }
+
+    /// Returns an iterator over the segments of the `ByteStr` separated by `delim`.
+    ///
+    /// Unlike splitting on every `delim`, a trailing `delim` does not produce
+    /// a final empty segment, matching `str::split_terminator`.
+    pub fn split_terminator(&self, delim: u8) -> SplitTerminator<'_> {
+        SplitTerminator { rest: &self.0, delim }
+    }
+
+    /// Returns a wrapper whose `Display` implementation outputs the `ByteStr` as UTF-8,
+    /// replacing invalid sequences with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Width, fill and alignment are honored as for `str`.
+    /// Unlike `String::from_utf8_lossy`, this only allocates
+    /// when padding or truncating input that is not valid UTF-8.
+    pub fn display_lossy(&self) -> impl Display + '_ {
+        DisplayLossy(self)
+    }
+
+    /// Splits the `ByteStr` into a slice of `N`-byte arrays,
+    /// starting at the beginning, and a remainder shorter than `N` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is 0.
+    pub fn as_chunks<const N: usize>(&self) -> (&[[u8; N]], &ByteStr) {
+        assert!(N != 0, "chunk size must be non-zero");
+        let chunks = self.0.chunks_exact(N);
+        let remainder = chunks.remainder();
+        let len = chunks.len();
+        // `[u8; N]` has the same alignment as `u8`,
+        // and the first `len * N` bytes are all in bounds.
+        let chunks = unsafe { std::slice::from_raw_parts(self.0.as_ptr() as *const [u8; N], len) };
+        (chunks, ByteStr::new(remainder))
+    }
+
+    /// Checks if the `ByteStr` contains the same bytes as `other`.
+    pub fn eq_bytes<B: AsRef<[u8]>>(&self, other: B) -> bool {
+        self.0 == *other.as_ref()
+    }
+
+    /// Returns the `ByteStr` with all leading and trailing occurrences of `b` removed.
+    pub fn trim_matches_byte(&self, b: u8) -> &ByteStr {
+        self.trim_start_matches_byte(b).trim_end_matches_byte(b)
+    }
+
+    /// Returns the `ByteStr` with all leading occurrences of `b` removed.
+    pub fn trim_start_matches_byte(&self, b: u8) -> &ByteStr {
+        let start = self.0.iter().position(|&x| x != b).unwrap_or(self.0.len());
+        ByteStr::new(&self.0[start..])
+    }
+
+    /// Returns the `ByteStr` with all trailing occurrences of `b` removed.
+    pub fn trim_end_matches_byte(&self, b: u8) -> &ByteStr {
+        let end = self.0.iter().rposition(|&x| x != b).map_or(0, |i| i + 1);
+        ByteStr::new(&self.0[..end])
+    }
+
+    /// Returns the `ByteStr` with all trailing NUL bytes removed.
+    pub fn trim_trailing_nuls(&self) -> &ByteStr {
+        self.trim_end_matches_byte(0)
+    }
+
+    /// Returns the bytes up to (but not including) the first NUL byte,
+    /// or the whole `ByteStr` if it contains no NUL byte.
+    pub fn as_cstr(&self) -> &ByteStr {
+        let end = self.0.iter().position(|&b| b == 0).unwrap_or(self.0.len());
+        ByteStr::new(&self.0[..end])
+    }
+
+    /// Returns the sub-`ByteStr` in `range`,
+    /// or an error describing why `range` is invalid for this `ByteStr`.
+    pub fn try_slice(&self, range: Range<usize>) -> Result<&ByteStr, RangeError> {
+        if range.start > range.end || range.end > self.0.len() {
+            return Err(RangeError {
+                start: range.start,
+                end: range.end,
+                len: self.0.len(),
+            });
+        }
+
+        Ok(ByteStr::new(&self.0[range]))
+    }
+
+    /// Splits the `ByteStr` on the first occurrence of the UTF-8 encoding of `delim`,
+    /// returning the parts before and after the delimiter.
+    ///
+    /// Returns `None` if `delim` doesn't occur in the `ByteStr`.
+    pub fn split_once_str(&self, delim: &str) -> Option<(&ByteStr, &ByteStr)> {
+        let index = find_slice(&self.0, delim.as_bytes())?;
+        Some((ByteStr::new(&self.0[..index]), ByteStr::new(&self.0[index + delim.len()..])))
+    }
+
+    /// Splits the `ByteStr` on the last occurrence of the UTF-8 encoding of `delim`,
+    /// returning the parts before and after the delimiter.
+    ///
+    /// Returns `None` if `delim` doesn't occur in the `ByteStr`.
+    pub fn rsplit_once_str(&self, delim: &str) -> Option<(&ByteStr, &ByteStr)> {
+        let index = rfind_slice(&self.0, delim.as_bytes())?;
+        Some((ByteStr::new(&self.0[..index]), ByteStr::new(&self.0[index + delim.len()..])))
+    }
+
+    /// Splits the `ByteStr` into a fixed-size header of `N` bytes and the remaining body.
+    ///
+    /// Returns `None` if the `ByteStr` is shorter than `N` bytes.
+    pub fn split_header<const N: usize>(&self) -> Option<(&[u8; N], &ByteStr)> {
+        self.0.split_first_chunk().map(|(header, body)| (header, ByteStr::new(body)))
+    }
+
+    /// Copies all bytes from `src` into the `ByteStr`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` doesn't have the same length as the `ByteStr`.
+    pub fn copy_from_slice(&mut self, src: &[u8]) {
+        self.0.copy_from_slice(src)
+    }
+
+    /// Clones all bytes from `src` into the `ByteStr`.
+    ///
+    /// For bytes, this is equivalent to `copy_from_slice`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` doesn't have the same length as the `ByteStr`.
+    pub fn clone_from_slice(&mut self, src: &[u8]) {
+        self.0.clone_from_slice(src)
+    }
+
+    /// Fills the `ByteStr` with `value`.
+    pub fn fill(&mut self, value: u8) {
+        self.0.fill(value)
+    }
+
+    /// Fills the `ByteStr` with bytes returned by calling `f` repeatedly.
+    pub fn fill_with<F>(&mut self, f: F)
+        where F: FnMut() -> u8
+    {
+        self.0.fill_with(f)
+    }
+
+    /// Splits the bytes of the `ByteStr` into two new `ByteString`s:
+    /// the bytes for which `pred` returns `true`, and those for which it returns `false`.
+    ///
+    /// The bytes keep their relative order in both results.
+    pub fn partition<F>(&self, mut pred: F) -> (ByteString, ByteString)
+        where F: FnMut(u8) -> bool
+    {
+        self.0.iter().partition(|&&b| pred(b))
+    }
+
+    /// Returns an iterator over the bytes of the `ByteStr`,
+    /// each escaped as with `std::ascii::escape_default`.
+    ///
+    /// The escaped bytes are the ones between the quotes in the `Debug` output.
+    pub fn escape_ascii(&self) -> std::slice::EscapeAscii<'_> {
+        self.0.escape_ascii()
+    }
+
+    /// Checks if the `ByteStr` starts with the UTF-8 encoding of `prefix`.
+    pub fn starts_with_str(&self, prefix: &str) -> bool {
+        self.0.starts_with(prefix.as_bytes())
+    }
+
+    /// Checks if the `ByteStr` ends with the UTF-8 encoding of `suffix`.
+    pub fn ends_with_str(&self, suffix: &str) -> bool {
+        self.0.ends_with(suffix.as_bytes())
+    }
+
+    /// Returns an iterator over exactly `size` bytes of the `ByteStr` at a time,
+    /// starting at the end.
+    ///
+    /// If `size` doesn't divide the length of the `ByteStr`,
+    /// the leftover bytes at the start are not yielded;
+    /// they can be retrieved with `RChunksExact::remainder`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn rchunks_exact(&self, size: usize) -> RChunksExact<'_> {
+        RChunksExact(self.0.rchunks_exact(size))
+    }
+
+    /// Appends the bytes of the `ByteStr` to `out` as lowercase hexadecimal digits,
+    /// two digits per byte.
+    pub fn encode_hex_into(&self, out: &mut String) {
+        encode_hex(&self.0, out, LOWER_HEX_DIGITS)
+    }
+
+    /// Appends the bytes of the `ByteStr` to `out` as uppercase hexadecimal digits,
+    /// two digits per byte.
+    pub fn encode_upper_hex_into(&self, out: &mut String) {
+        encode_hex(&self.0, out, UPPER_HEX_DIGITS)
+    }
+
+    /// Splits the `ByteStr` into an unaligned head, a slice of aligned `u32` words
+    /// and an unaligned tail.
+    ///
+    /// The words are read in native byte order.
+    /// The head and tail are as short as possible,
+    /// but the middle is not guaranteed to be non-empty.
+    pub fn align_to_u32(&self) -> (&ByteStr, &[u32], &ByteStr) {
+        // Every bit pattern is a valid u32 and align_to respects u32's alignment,
+        // so reinterpreting the middle bytes is sound.
+        let (head, words, tail) = unsafe { self.0.align_to::<u32>() };
+        (ByteStr::new(head), words, ByteStr::new(tail))
+    }
+
+    /// Returns a copy of the `ByteStr` with ASCII letters converted to lowercase.
+    ///
+    /// Non-ASCII bytes are left unchanged.
+    pub fn to_ascii_lowercase(&self) -> ByteString {
+        ByteString::new(self.0.to_ascii_lowercase())
+    }
+
+    /// Returns a copy of the `ByteStr` with ASCII letters converted to uppercase.
+    ///
+    /// Non-ASCII bytes are left unchanged.
+    pub fn to_ascii_uppercase(&self) -> ByteString {
+        ByteString::new(self.0.to_ascii_uppercase())
+    }
+
+    /// Returns the escaped form of the `ByteStr` (as produced by `escape_ascii`),
+    /// truncated to at most `max_cols` characters.
+    ///
+    /// If the escaped form doesn't fit, as many whole escape sequences as possible are kept
+    /// and `…` is appended; the ellipsis counts as one column.
+    /// An escape sequence such as `\xNN` is never split.
+    pub fn truncate_escaped_to_width(&self, max_cols: usize) -> String {
+        let mut out = String::new();
+        let mut fitting_len = None;
+        for &byte in &self.0 {
+            let escaped = std::ascii::escape_default(byte);
+            if fitting_len.is_none() && out.len() + escaped.len() > max_cols.saturating_sub(1) {
+                fitting_len = Some(out.len());
+            }
+
+            out.extend(escaped.map(char::from));
+            if out.len() > max_cols {
+                out.truncate(fitting_len.unwrap_or(0));
+                if max_cols > 0 {
+                    out.push('\u{2026}');
+                }
+
+                break;
+            }
+        }
+
+        out
+    }
+
+    /// Checks if `needle` occurs anywhere in the `ByteStr`.
+    ///
+    /// An empty `needle` is always found.
+    pub fn contains_slice<B: AsRef<[u8]>>(&self, needle: B) -> bool {
+        find_slice(&self.0, needle.as_ref()).is_some()
+    }
+
+    /// Rotates the `ByteStr` in place so that the byte at `mid` becomes the first byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the length of the `ByteStr`.
+    pub fn rotate_left(&mut self, mid: usize) {
+        self.0.rotate_left(mid)
+    }
+
+    /// Rotates the `ByteStr` in place so that the last `k` bytes move to the front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is greater than the length of the `ByteStr`.
+    pub fn rotate_right(&mut self, k: usize) {
+        self.0.rotate_right(k)
+    }
+
+    /// Returns a new `ByteString` containing each distinct byte of the `ByteStr` once,
+    /// in ascending order.
+    pub fn distinct_sorted(&self) -> ByteString {
+        let mut seen = [false; 256];
+        for &byte in &self.0 {
+            seen[byte as usize] = true;
+        }
+
+        (0..=255u8).filter(|&byte| seen[byte as usize]).collect()
+    }
+
+    /// Returns an iterator over the bytes of the `ByteStr` as lowercase hexadecimal digits,
+    /// two digits per byte.
+    pub fn hex_chars(&self) -> HexChars<'_> {
+        HexChars {
+            bytes: self.0.iter(),
+            digits: LOWER_HEX_DIGITS,
+            pending: None,
+        }
+    }
+
+    /// Returns an iterator over the bytes of the `ByteStr` as uppercase hexadecimal digits,
+    /// two digits per byte.
+    pub fn upper_hex_chars(&self) -> HexChars<'_> {
+        HexChars {
+            bytes: self.0.iter(),
+            digits: UPPER_HEX_DIGITS,
+            pending: None,
+        }
+    }
+
+    /// Checks if the `ByteStr` is equal to `other` when only the bits set in `mask` are compared.
+    ///
+    /// Returns `false` if `other` or `mask` doesn't have the same length as the `ByteStr`.
+    pub fn equal_under_mask(&self, other: &[u8], mask: &[u8]) -> bool {
+        self.0.len() == other.len() && self.0.len() == mask.len()
+            && self.0.iter().zip(other).zip(mask).all(|((&a, &b), &m)| a & m == b & m)
+    }
+
+    /// Returns the byte at `index`, or `None` if `index` is out of bounds.
+    pub fn byte_at(&self, index: usize) -> Option<u8> {
+        self.0.get(index).cloned()
+    }
+
+    /// Returns a copy of the `ByteStr` as valid UTF-8,
+    /// replacing invalid sequences with the encoding of U+FFFD REPLACEMENT CHARACTER.
+    pub fn to_utf8_lossy_bytes(&self) -> ByteString {
+        ByteString::new(String::from_utf8_lossy(&self.0).into_owned().into_bytes())
+    }
+
+    /// Returns the number of consecutive `b` bytes at the start of the `ByteStr`.
+    pub fn count_leading(&self, b: u8) -> usize {
+        self.0.iter().take_while(|&&x| x == b).count()
+    }
+
+    /// Returns the number of consecutive `b` bytes at the end of the `ByteStr`.
+    pub fn count_trailing(&self, b: u8) -> usize {
+        self.0.iter().rev().take_while(|&&x| x == b).count()
+    }
+
+    /// Splits the `ByteStr` at the first occurrence of `b`,
+    /// with the delimiter starting the second part.
+    ///
+    /// Returns `None` if `b` doesn't occur in the `ByteStr`.
+    pub fn split_before_byte(&self, b: u8) -> Option<(&ByteStr, &ByteStr)> {
+        let index = self.0.iter().position(|&x| x == b)?;
+        let (head, tail) = self.0.split_at(index);
+        Some((ByteStr::new(head), ByteStr::new(tail)))
+    }
+
+    /// Splits the `ByteStr` at the first occurrence of `b`,
+    /// with the delimiter ending the first part.
+    ///
+    /// Returns `None` if `b` doesn't occur in the `ByteStr`.
+    pub fn split_after_byte(&self, b: u8) -> Option<(&ByteStr, &ByteStr)> {
+        let index = self.0.iter().position(|&x| x == b)?;
+        let (head, tail) = self.0.split_at(index + 1);
+        Some((ByteStr::new(head), ByteStr::new(tail)))
+    }
+
+    /// Parses the `ByteStr` as an unsigned integer in the given radix,
+    /// following the same rules as `u64::from_str_radix`,
+    /// except that a leading `+` sign is rejected.
+    ///
+    /// Empty input, invalid digits (including non-ASCII bytes and signs)
+    /// and values that overflow a `u64` are rejected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    pub fn parse_u64(&self, radix: u32) -> Result<u64, ParseIntError> {
+        // Invalid UTF-8 becomes U+FFFD, which is never a valid digit.
+        let digits = String::from_utf8_lossy(&self.0);
+        if digits.starts_with('+') {
+            // u64::from_str_radix skips a leading '+'; report it as an invalid digit instead.
+            return u64::from_str_radix("\u{FFFD}", radix);
+        }
+        u64::from_str_radix(&digits, radix)
+    }
+
+    /// Returns an iterator over `size` bytes of the `ByteStr` at a time,
+    /// as mutable `ByteStr`s, starting at the beginning.
+    ///
+    /// The last chunk may be shorter than `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks_mut(&mut self, size: usize) -> ChunksMut<'_> {
+        ChunksMut(self.0.chunks_mut(size))
+    }
+
+    /// Returns the first byte of the `ByteStr`, or `None` if it is empty.
+    pub fn first_byte(&self) -> Option<u8> {
+        self.0.first().cloned()
+    }
+
+    /// Returns the last byte of the `ByteStr`, or `None` if it is empty.
+    pub fn last_byte(&self) -> Option<u8> {
+        self.0.last().cloned()
+    }
+
+    /// Checks if the `ByteStr` is equal to `other`
+    /// in time that depends only on the length of the inputs, not on their contents.
+    ///
+    /// This is intended for comparing secrets such as MACs.
+    /// The lengths are not secret: if they differ, this returns `false` immediately.
+    /// This is a best-effort implementation without compiler or hardware guarantees;
+    /// use a dedicated cryptography crate if stronger assurances are required.
+    pub fn ct_eq(&self, other: &[u8]) -> bool {
+        if self.0.len() != other.len() {
+            return false;
+        }
+
+        let mut difference = 0;
+        for (&a, &b) in self.0.iter().zip(other) {
+            difference |= a ^ b;
+        }
+
+        std::hint::black_box(difference) == 0
+    }
+
+    /// Computes the CRC-32 checksum (IEEE polynomial, as used by zlib and PNG) of the bytes.
+    #[cfg(feature = "crc")]
+    pub fn crc32(&self) -> u32 {
+        let mut crc = !0u32;
+        for &b in &self.0 {
+            crc ^= u32::from(b);
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    /// Returns a new `ByteString` with `f` applied to each byte of the `ByteStr`.
+    pub fn map_bytes<F>(&self, f: F) -> ByteString
+        where F: FnMut(u8) -> u8
+    {
+        self.0.iter().cloned().map(f).collect()
+    }
 }
 
 impl<'a> From<&'a [u8]> for &'a ByteStr {
@@ -83,6 +773,32 @@ impl<'a> From<&'a mut ByteStr> for &'a mut [u8] {
     }
 }
 
+impl From<Box<[u8]>> for Box<ByteStr> {
+    fn from(s: Box<[u8]>) -> Box<ByteStr> {
+        // ByteStr is a transparent wrapper around [u8], so the layouts match.
+        unsafe { Box::from_raw(Box::into_raw(s) as *mut ByteStr) }
+    }
+}
+
+impl From<Box<ByteStr>> for Box<[u8]> {
+    fn from(s: Box<ByteStr>) -> Box<[u8]> {
+        // ByteStr is a transparent wrapper around [u8], so the layouts match.
+        unsafe { Box::from_raw(Box::into_raw(s) as *mut [u8]) }
+    }
+}
+
+impl From<Box<ByteStr>> for Vec<u8> {
+    fn from(s: Box<ByteStr>) -> Vec<u8> {
+        Box::<[u8]>::from(s).into_vec()
+    }
+}
+
+impl Borrow<ByteStr> for Vec<u8> {
+    fn borrow(&self) -> &ByteStr {
+        ByteStr::new(self)
+    }
+}
+
 impl AsRef<[u8]> for ByteStr {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -119,6 +835,36 @@ impl PartialEq<ByteStr> for [u8] {
     }
 }
 
+impl<'a> PartialEq<&'a [u8]> for ByteStr {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        &self.0 == *other
+    }
+}
+
+impl PartialEq<str> for ByteStr {
+    fn eq(&self, other: &str) -> bool {
+        &self.0 == other.as_bytes()
+    }
+}
+
+impl<'a> PartialEq<&'a str> for ByteStr {
+    fn eq(&self, other: &&'a str) -> bool {
+        &self.0 == other.as_bytes()
+    }
+}
+
+impl<'a> PartialEq<Cow<'a, [u8]>> for ByteStr {
+    fn eq(&self, other: &Cow<'a, [u8]>) -> bool {
+        self.0 == **other
+    }
+}
+
+impl<'a> PartialOrd<Cow<'a, [u8]>> for ByteStr {
+    fn partial_cmp(&self, other: &Cow<'a, [u8]>) -> Option<Ordering> {
+        self.0.partial_cmp(&**other)
+    }
+}
+
 impl Deref for ByteStr {
     type Target = [u8];
 
@@ -133,6 +879,67 @@ impl DerefMut for ByteStr {
     }
 }
 
+/// Indexes the `ByteStr` like a byte slice.
+///
+/// Indexing with a `usize` yields a `u8`;
+/// indexing with a range yields a `ByteStr`.
+impl<I> Index<I> for ByteStr
+    where I: SliceIndex<[u8]>,
+          I::Output: index::IndexOutput
+{
+    type Output = <I::Output as index::IndexOutput>::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        index::IndexOutput::wrap(&self.0[index])
+    }
+}
+
+impl<I> IndexMut<I> for ByteStr
+    where I: SliceIndex<[u8]>,
+          I::Output: index::IndexOutput
+{
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        index::IndexOutput::wrap_mut(&mut self.0[index])
+    }
+}
+
+mod index {
+    use super::ByteStr;
+
+    /// Maps the output of indexing a byte slice
+    /// to the output of indexing a `ByteStr`.
+    pub trait IndexOutput: 'static {
+        type Output: ?Sized;
+
+        fn wrap(&self) -> &Self::Output;
+        fn wrap_mut(&mut self) -> &mut Self::Output;
+    }
+
+    impl IndexOutput for u8 {
+        type Output = u8;
+
+        fn wrap(&self) -> &u8 {
+            self
+        }
+
+        fn wrap_mut(&mut self) -> &mut u8 {
+            self
+        }
+    }
+
+    impl IndexOutput for [u8] {
+        type Output = ByteStr;
+
+        fn wrap(&self) -> &ByteStr {
+            ByteStr::new(self)
+        }
+
+        fn wrap_mut(&mut self) -> &mut ByteStr {
+            ByteStr::new_mut(self)
+        }
+    }
+}
+
 impl<'a> Default for &'a ByteStr {
     fn default() -> &'a ByteStr {
         ByteStr::new(&[])
@@ -141,7 +948,21 @@ impl<'a> Default for &'a ByteStr {
 
 impl<'a> Default for &'a mut ByteStr {
     fn default() -> &'a mut ByteStr {
-        ByteStr::new_mut(&mut [])
+        // A mutable borrow of an empty array literal is promoted to `'static`,
+        // so the empty slice is valid for any `'a`.
+        let empty: &'static mut [u8] = &mut [];
+        ByteStr::new_mut(empty)
+    }
+}
+
+impl Add<&ByteStr> for &ByteStr {
+    type Output = ByteString;
+
+    fn add(self, other: &ByteStr) -> ByteString {
+        let mut result = Vec::with_capacity(self.0.len() + other.0.len());
+        result.extend_from_slice(&self.0);
+        result.extend_from_slice(&other.0);
+        ByteString::new(result)
     }
 }
 
@@ -150,7 +971,7 @@ impl<'a> IntoIterator for &'a ByteStr {
     type IntoIter = std::slice::Iter<'a, u8>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.0.iter()
     }
 }
 
@@ -159,276 +980,2698 @@ impl<'a> IntoIterator for &'a mut ByteStr {
     type IntoIter = std::slice::IterMut<'a, u8>;
 
     fn into_iter(self) -> Self::IntoIter {
-        (&mut self.0).into_iter()
+        self.0.iter_mut()
     }
 }
 
 impl Debug for ByteStr {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        try!(write!(f, "b\""));
+        write!(f, "b\"")?;
 
-        for &byte in self {
-            for ch in std::ascii::escape_default(byte) {
-                try!(write!(f, "{}", ch as char));
+        let mut rest = &self.0;
+        while !rest.is_empty() {
+            // Write runs of bytes that escape_default leaves unchanged in a single call.
+            let run = rest.iter().position(|&b| !is_unescaped(b)).unwrap_or(rest.len());
+            if run > 0 {
+                // The run only contains printable ASCII characters, which are valid UTF-8.
+                f.write_str(unsafe { std::str::from_utf8_unchecked(&rest[..run]) })?;
+                rest = &rest[run..];
+                continue;
+            }
+
+            for ch in std::ascii::escape_default(rest[0]) {
+                write!(f, "{}", ch as char)?;
             }
+
+            rest = &rest[1..];
         }
 
         write!(f, "\"")
     }
 }
 
-impl ByteString {
-    /// Moves a vector of bytes to a new `ByteString`.
-    pub fn new(s: Vec<u8>) -> ByteString {
-        ByteString(s)
+/// Outputs the bytes as pairs of hexadecimal digits.
+///
+/// If a nonzero precision is specified (e.g. `{:.2x}`),
+/// a space is inserted between each group of that many bytes.
+impl LowerHex for ByteStr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fmt_hex(&self.0, f, LOWER_HEX_DIGITS)
     }
 }
 
-impl From<Vec<u8>> for ByteString {
-    fn from(s: Vec<u8>) -> ByteString {
-        ByteString::new(s)
+/// Outputs the bytes as pairs of hexadecimal digits.
+///
+/// If a nonzero precision is specified (e.g. `{:.2X}`),
+/// a space is inserted between each group of that many bytes.
+impl UpperHex for ByteStr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fmt_hex(&self.0, f, UPPER_HEX_DIGITS)
     }
 }
 
-impl From<ByteString> for Vec<u8> {
-    fn from(s: ByteString) -> Vec<u8> {
-        s.0
+fn fmt_hex(bytes: &[u8], f: &mut Formatter, digits: &[u8; 16]) -> Result<(), Error> {
+    let group = f.precision().unwrap_or(0);
+    for (i, &byte) in bytes.iter().enumerate() {
+        if group > 0 && i > 0 && i % group == 0 {
+            f.write_char(' ')?;
+        }
+
+        f.write_char(digits[(byte >> 4) as usize] as char)?;
+        f.write_char(digits[(byte & 0xf) as usize] as char)?;
     }
+
+    Ok(())
 }
 
-impl AsRef<Vec<u8>> for ByteString {
-    fn as_ref(&self) -> &Vec<u8> {
-        &self.0
+/// Returns whether `std::ascii::escape_default` outputs `byte` unchanged.
+fn is_unescaped(byte: u8) -> bool {
+    match byte {
+        b'\\' | b'"' | b'\'' => false,
+        0x20..=0x7e => true,
+        _ => false,
     }
 }
 
-impl AsRef<[u8]> for ByteString {
-    fn as_ref(&self) -> &[u8] {
-        &self.0
+const LOWER_HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const UPPER_HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+fn encode_hex(bytes: &[u8], out: &mut String, digits: &[u8; 16]) {
+    out.reserve(bytes.len() * 2);
+    for &byte in bytes {
+        out.push(digits[(byte >> 4) as usize] as char);
+        out.push(digits[(byte & 0xf) as usize] as char);
     }
 }
 
-impl AsMut<Vec<u8>> for ByteString {
-    fn as_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.0
+/// Returns the index of the first occurrence of `needle` in `haystack`.
+fn find_slice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
     }
+
+    haystack.windows(needle.len()).position(|window| window == needle)
 }
 
-impl AsMut<[u8]> for ByteString {
-    fn as_mut(&mut self) -> &mut [u8] {
-        &mut self.0
+/// Returns the index of the last occurrence of `needle` in `haystack`.
+fn rfind_slice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(haystack.len());
     }
+
+    haystack.windows(needle.len()).rposition(|window| window == needle)
 }
 
-impl Borrow<ByteStr> for ByteString {
-    fn borrow(&self) -> &ByteStr {
-        ByteStr::new(&self.0)
+/// An iterator over a `ByteStr` in non-overlapping chunks, starting at the end.
+///
+/// This struct is created by the `rchunks` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct RChunks<'a>(std::slice::RChunks<'a, u8>);
+
+impl<'a> Iterator for RChunks<'a> {
+    type Item = &'a ByteStr;
+
+    fn next(&mut self) -> Option<&'a ByteStr> {
+        self.0.next().map(ByteStr::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
     }
 }
 
-impl Borrow<Vec<u8>> for ByteString {
-    fn borrow(&self) -> &Vec<u8> {
-        &self.0
+impl<'a> DoubleEndedIterator for RChunks<'a> {
+    fn next_back(&mut self) -> Option<&'a ByteStr> {
+        self.0.next_back().map(ByteStr::new)
     }
 }
 
-impl Borrow<[u8]> for ByteString {
-    fn borrow(&self) -> &[u8] {
-        &self.0
+impl<'a> ExactSizeIterator for RChunks<'a> {}
+
+/// An iterator over a `ByteStr` in non-overlapping chunks of exactly the same size,
+/// starting at the end.
+///
+/// This struct is created by the `rchunks_exact` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct RChunksExact<'a>(std::slice::RChunksExact<'a, u8>);
+
+impl<'a> RChunksExact<'a> {
+    /// Returns the bytes at the start of the `ByteStr`
+    /// that are not part of any chunk.
+    pub fn remainder(&self) -> &'a ByteStr {
+        ByteStr::new(self.0.remainder())
     }
 }
 
-impl BorrowMut<ByteStr> for ByteString {
-    fn borrow_mut(&mut self) -> &mut ByteStr {
-        ByteStr::new_mut(&mut self.0)
+impl<'a> Iterator for RChunksExact<'a> {
+    type Item = &'a ByteStr;
+
+    fn next(&mut self) -> Option<&'a ByteStr> {
+        self.0.next().map(ByteStr::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
     }
 }
 
-impl BorrowMut<Vec<u8>> for ByteString {
-    fn borrow_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.0
+impl<'a> DoubleEndedIterator for RChunksExact<'a> {
+    fn next_back(&mut self) -> Option<&'a ByteStr> {
+        self.0.next_back().map(ByteStr::new)
     }
 }
 
-impl BorrowMut<[u8]> for ByteString {
-    fn borrow_mut(&mut self) -> &mut [u8] {
-        &mut self.0
+impl<'a> ExactSizeIterator for RChunksExact<'a> {}
+
+/// An iterator over a `ByteStr` in non-overlapping mutable chunks,
+/// starting at the beginning.
+///
+/// This struct is created by the `chunks_mut` method on `ByteStr`.
+#[derive(Debug)]
+pub struct ChunksMut<'a>(std::slice::ChunksMut<'a, u8>);
+
+impl<'a> Iterator for ChunksMut<'a> {
+    type Item = &'a mut ByteStr;
+
+    fn next(&mut self) -> Option<&'a mut ByteStr> {
+        self.0.next().map(ByteStr::new_mut)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
     }
 }
 
-impl PartialEq<Vec<u8>> for ByteString {
-    fn eq(&self, other: &Vec<u8>) -> bool {
-        self.0 == *other
+impl<'a> DoubleEndedIterator for ChunksMut<'a> {
+    fn next_back(&mut self) -> Option<&'a mut ByteStr> {
+        self.0.next_back().map(ByteStr::new_mut)
     }
 }
 
-impl PartialEq<[u8]> for ByteString {
-    fn eq(&self, other: &[u8]) -> bool {
-        self.0 == other
+impl<'a> ExactSizeIterator for ChunksMut<'a> {}
+
+/// An iterator over the non-overlapping occurrences of a needle in a `ByteStr`
+/// and their starting index.
+///
+/// This struct is created by the `match_indices` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct MatchIndices<'a> {
+    haystack: &'a [u8],
+    needle: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Iterator for MatchIndices<'a> {
+    type Item = (usize, &'a ByteStr);
+
+    fn next(&mut self) -> Option<(usize, &'a ByteStr)> {
+        if self.needle.is_empty() {
+            if self.position > self.haystack.len() {
+                return None;
+            }
+
+            let index = self.position;
+            self.position += 1;
+            return Some((index, ByteStr::new(self.needle)));
+        }
+
+        let offset = self.haystack.get(self.position..)?
+            .windows(self.needle.len())
+            .position(|window| window == self.needle);
+        match offset {
+            Some(offset) => {
+                let index = self.position + offset;
+                self.position = index + self.needle.len();
+                Some((index, ByteStr::new(&self.haystack[index..self.position])))
+            }
+            None => {
+                self.position = self.haystack.len() + 1;
+                None
+            }
+        }
     }
 }
 
-impl PartialEq<ByteString> for Vec<u8> {
-    fn eq(&self, other: &ByteString) -> bool {
-        self == &other.0
+/// An iterator over the non-overlapping occurrences of a needle in a `ByteStr`.
+///
+/// This struct is created by the `matches` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct Matches<'a>(MatchIndices<'a>);
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = &'a ByteStr;
+
+    fn next(&mut self) -> Option<&'a ByteStr> {
+        self.0.next().map(|(_, m)| m)
     }
 }
 
-impl PartialEq<ByteString> for [u8] {
-    fn eq(&self, other: &ByteString) -> bool {
-        self == &other.0[..]
+/// An iterator over the non-whitespace runs of a `ByteStr`.
+///
+/// This struct is created by the `split_ascii_whitespace` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct SplitAsciiWhitespace<'a>(&'a [u8]);
+
+impl<'a> Iterator for SplitAsciiWhitespace<'a> {
+    type Item = &'a ByteStr;
+
+    fn next(&mut self) -> Option<&'a ByteStr> {
+        let start = self.0.iter().position(|b| !b.is_ascii_whitespace())?;
+        let rest = &self.0[start..];
+        let end = rest.iter().position(u8::is_ascii_whitespace).unwrap_or(rest.len());
+        self.0 = &rest[end..];
+        Some(ByteStr::new(&rest[..end]))
     }
 }
 
-impl Deref for ByteString {
-    type Target = Vec<u8>;
+/// An iterator over the `delim`-terminated segments of a `ByteStr`.
+///
+/// This struct is created by the `split_terminator` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct SplitTerminator<'a> {
+    rest: &'a [u8],
+    delim: u8,
+}
 
-    fn deref(&self) -> &Vec<u8> {
-        &self.0
+impl<'a> Iterator for SplitTerminator<'a> {
+    type Item = &'a ByteStr;
+
+    fn next(&mut self) -> Option<&'a ByteStr> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let segment = match self.rest.iter().position(|&b| b == self.delim) {
+            Some(i) => {
+                let segment = &self.rest[..i];
+                self.rest = &self.rest[i + 1..];
+                segment
+            }
+            None => mem::take(&mut self.rest),
+        };
+        Some(ByteStr::new(segment))
     }
 }
 
-impl DerefMut for ByteString {
-    fn deref_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.0
+/// An iterator over the bytes of a `ByteStr` as hexadecimal digits.
+///
+/// This struct is created by the `hex_chars` and `upper_hex_chars` methods on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct HexChars<'a> {
+    bytes: std::slice::Iter<'a, u8>,
+    digits: &'static [u8; 16],
+    pending: Option<char>,
+}
+
+impl<'a> Iterator for HexChars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if let Some(ch) = self.pending.take() {
+            return Some(ch);
+        }
+
+        let byte = *self.bytes.next()?;
+        self.pending = Some(self.digits[(byte & 0xf) as usize] as char);
+        Some(self.digits[(byte >> 4) as usize] as char)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bytes.len() * 2 + self.pending.is_some() as usize;
+        (len, Some(len))
     }
 }
 
-impl Default for ByteString {
-    fn default() -> ByteString {
-        ByteString::new(vec![])
+impl<'a> ExactSizeIterator for HexChars<'a> {}
+
+/// An iterator over a `ByteStr` split into chunks of valid UTF-8
+/// followed by invalid bytes.
+///
+/// This struct is created by the `utf8_chunks` method on `ByteStr`.
+#[derive(Clone, Debug)]
+pub struct Utf8Chunks<'a>(std::str::Utf8Chunks<'a>);
+
+impl<'a> Iterator for Utf8Chunks<'a> {
+    type Item = Utf8Chunk<'a>;
+
+    fn next(&mut self) -> Option<Utf8Chunk<'a>> {
+        self.0.next().map(|chunk| Utf8Chunk {
+            valid: chunk.valid(),
+            invalid: ByteStr::new(chunk.invalid()),
+        })
     }
 }
 
-impl FromIterator<u8> for ByteString {
-    fn from_iter<I>(iter: I) -> ByteString
-        where I: IntoIterator<Item=u8>
-    {
-        ByteString::new(Vec::from_iter(iter))
+/// A chunk of a `ByteStr` yielded by `Utf8Chunks`:
+/// a run of valid UTF-8 followed by a run of invalid bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Utf8Chunk<'a> {
+    valid: &'a str,
+    invalid: &'a ByteStr,
+}
+
+impl<'a> Utf8Chunk<'a> {
+    /// Returns the valid UTF-8 at the start of the chunk.
+    pub fn valid(&self) -> &'a str {
+        self.valid
+    }
+
+    /// Returns the invalid bytes at the end of the chunk.
+    ///
+    /// This is empty only for the last chunk,
+    /// and contains at most 3 bytes.
+    pub fn invalid(&self) -> &'a ByteStr {
+        self.invalid
     }
 }
 
-impl<'a> IntoIterator for ByteString {
-    type Item = u8;
-    type IntoIter = std::vec::IntoIter<u8>;
+struct DebugUtf8Aware<'a>(&'a ByteStr);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+impl<'a> Debug for DebugUtf8Aware<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "b\"")?;
+
+        for chunk in self.0.0.utf8_chunks() {
+            for ch in chunk.valid().chars() {
+                if ch.is_ascii() {
+                    for escaped in std::ascii::escape_default(ch as u8) {
+                        write!(f, "{}", escaped as char)?;
+                    }
+                } else if ch.is_control() {
+                    for byte in ch.encode_utf8(&mut [0; 4]).bytes() {
+                        write!(f, "\\x{:02x}", byte)?;
+                    }
+                } else {
+                    write!(f, "{}", ch)?;
+                }
+            }
+
+            for byte in chunk.invalid() {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+
+        write!(f, "\"")
     }
 }
 
-impl<'a> IntoIterator for &'a ByteString {
-    type Item = &'a u8;
-    type IntoIter = std::slice::Iter<'a, u8>;
+struct DebugWrapped<'a> {
+    bytes: &'a ByteStr,
+    width: usize,
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        (&self.0).into_iter()
+impl<'a> Debug for DebugWrapped<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "b\"")?;
+
+        let mut column = 0;
+        for &byte in self.bytes {
+            let escaped = std::ascii::escape_default(byte);
+            if column > 0 && column + escaped.len() > self.width {
+                f.write_char('\n')?;
+                column = 0;
+            }
+
+            column += escaped.len();
+            for ch in escaped {
+                f.write_char(ch as char)?;
+            }
+        }
+
+        write!(f, "\"")
     }
 }
 
-impl<'a> IntoIterator for &'a mut ByteString {
-    type Item = &'a mut u8;
-    type IntoIter = std::slice::IterMut<'a, u8>;
+struct DisplayLossy<'a>(&'a ByteStr);
 
-    fn into_iter(self) -> Self::IntoIter {
-        (&mut self.0).into_iter()
+impl<'a> Display for DisplayLossy<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if let Ok(s) = std::str::from_utf8(&self.0.0) {
+            return f.pad(s);
+        }
+
+        if f.width().is_some() || f.precision().is_some() {
+            return f.pad(&String::from_utf8_lossy(&self.0.0));
+        }
+
+        for chunk in self.0.utf8_chunks() {
+            f.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                f.write_char('\u{FFFD}')?;
+            }
+        }
+
+        Ok(())
     }
 }
 
-impl Debug for ByteString {
+/// The error returned by `ByteStr::try_slice` when a range is out of bounds
+/// or its start is greater than its end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeError {
+    /// The start of the requested range.
+    pub start: usize,
+    /// The end of the requested range.
+    pub end: usize,
+    /// The length of the `ByteStr` that was sliced.
+    pub len: usize,
+}
+
+impl Display for RangeError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        // Delegate to ByteStr's implementation
-        Debug::fmt(Borrow::<ByteStr>::borrow(self), f)
+        if self.start > self.end {
+            write!(f, "range start {} is greater than range end {}", self.start, self.end)
+        } else {
+            write!(f, "range end {} is out of bounds for length {}", self.end, self.len)
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl std::error::Error for RangeError {}
 
-    const EMPTY: &'static str = "b\"\"";
-    const ALL_BYTES: &'static str = concat!("b\"",
-        "\\x00\\x01\\x02\\x03\\x04\\x05\\x06\\x07\\x08\\t\\n\\x0b\\x0c\\r\\x0e\\x0f",
-        "\\x10\\x11\\x12\\x13\\x14\\x15\\x16\\x17\\x18\\x19\\x1a\\x1b\\x1c\\x1d\\x1e\\x1f",
-        " !\\\"#$%&\\\'()*+,-./",
-        "0123456789:;<=>?",
-        "@ABCDEFGHIJKLMNO",
-        "PQRSTUVWXYZ[\\\\]^_",
-        "`abcdefghijklmno",
-        "pqrstuvwxyz{|}~\\x7f",
-        "\\x80\\x81\\x82\\x83\\x84\\x85\\x86\\x87\\x88\\x89\\x8a\\x8b\\x8c\\x8d\\x8e\\x8f",
-        "\\x90\\x91\\x92\\x93\\x94\\x95\\x96\\x97\\x98\\x99\\x9a\\x9b\\x9c\\x9d\\x9e\\x9f",
-        "\\xa0\\xa1\\xa2\\xa3\\xa4\\xa5\\xa6\\xa7\\xa8\\xa9\\xaa\\xab\\xac\\xad\\xae\\xaf",
-        "\\xb0\\xb1\\xb2\\xb3\\xb4\\xb5\\xb6\\xb7\\xb8\\xb9\\xba\\xbb\\xbc\\xbd\\xbe\\xbf",
-        "\\xc0\\xc1\\xc2\\xc3\\xc4\\xc5\\xc6\\xc7\\xc8\\xc9\\xca\\xcb\\xcc\\xcd\\xce\\xcf",
-        "\\xd0\\xd1\\xd2\\xd3\\xd4\\xd5\\xd6\\xd7\\xd8\\xd9\\xda\\xdb\\xdc\\xdd\\xde\\xdf",
-        "\\xe0\\xe1\\xe2\\xe3\\xe4\\xe5\\xe6\\xe7\\xe8\\xe9\\xea\\xeb\\xec\\xed\\xee\\xef",
-        "\\xf0\\xf1\\xf2\\xf3\\xf4\\xf5\\xf6\\xf7\\xf8\\xf9\\xfa\\xfb\\xfc\\xfd\\xfe\\xff",
-        "\"");
+impl ByteString {
+    /// Moves a vector of bytes to a new `ByteString`.
+    pub fn new(s: Vec<u8>) -> ByteString {
+        ByteString(s)
+    }
 
-    #[test]
-    fn debug_bytestr_empty() {
-        let bytes = [];
-        let bs = ByteStr::new(&bytes);
-        let result = format!("{:?}", bs);
-        assert_eq!(result, EMPTY);
+    /// Consumes and leaks the `ByteString`,
+    /// returning a mutable reference to its contents.
+    ///
+    /// The buffer is never freed;
+    /// this is intended for data that lives for the remainder of the program,
+    /// such as arenas or global caches.
+    /// Dropping the returned reference does not release the memory.
+    pub fn leak(self) -> &'static mut ByteStr {
+        ByteStr::new_mut(self.0.leak())
     }
 
-    #[test]
-    fn debug_bytestr() {
-        let bytes = [
-            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
-            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F,
-            0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x2B, 0x2C, 0x2D, 0x2E, 0x2F,
-            0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
-            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F,
-            0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E, 0x5F,
-            0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F,
-            0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x7B, 0x7C, 0x7D, 0x7E, 0x7F,
-            0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x8D, 0x8E, 0x8F,
-            0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B, 0x9C, 0x9D, 0x9E, 0x9F,
-            0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xAE, 0xAF,
-            0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF,
-            0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE, 0xCF,
-            0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xDB, 0xDC, 0xDD, 0xDE, 0xDF,
-            0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xEB, 0xEC, 0xED, 0xEE, 0xEF,
-            0xF0, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0xFE, 0xFF,
-        ];
-        let bs = ByteStr::new(&bytes);
-        let result = format!("{:?}", bs);
-        assert_eq!(result, ALL_BYTES);
+    /// Checks that all bytes in the `ByteString` are ASCII.
+    ///
+    /// Returns the original `ByteString` as the error
+    /// if it contains any byte above 0x7F.
+    pub fn into_ascii(self) -> Result<ByteString, ByteString> {
+        if self.0.is_ascii() {
+            Ok(self)
+        } else {
+            Err(self)
+        }
     }
 
-    #[test]
-    fn debug_bytestring_empty() {
-        let bytes = vec![];
-        let bs = ByteString::new(bytes);
-        let result = format!("{:?}", bs);
-        assert_eq!(result, EMPTY);
+    /// Replaces the bytes in `range` with `replacement`,
+    /// growing or shrinking the `ByteString` as needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of `range` is greater than its end
+    /// or if its end is greater than the length of the `ByteString`.
+    pub fn replace_range(&mut self, range: Range<usize>, replacement: &[u8]) {
+        let removed = range.end.saturating_sub(range.start);
+        if replacement.len() > removed {
+            self.0.reserve(replacement.len() - removed);
+        }
+
+        self.0.splice(range, replacement.iter().cloned());
     }
 
-    #[test]
-    fn debug_bytestring() {
-        let bytes = vec![
-            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
-            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F,
-            0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x2B, 0x2C, 0x2D, 0x2E, 0x2F,
-            0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
-            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F,
-            0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E, 0x5F,
-            0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F,
-            0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x7B, 0x7C, 0x7D, 0x7E, 0x7F,
-            0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x8D, 0x8E, 0x8F,
-            0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B, 0x9C, 0x9D, 0x9E, 0x9F,
-            0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xAE, 0xAF,
-            0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF,
-            0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE, 0xCF,
-            0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xDB, 0xDC, 0xDD, 0xDE, 0xDF,
-            0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xEB, 0xEC, 0xED, 0xEE, 0xEF,
-            0xF0, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0xFE, 0xFF,
-        ];
-        let bs = ByteString::new(bytes);
-        let result = format!("{:?}", bs);
-        assert_eq!(result, ALL_BYTES);
+    /// Reads all bytes from `r` until EOF into a new `ByteString`.
+    ///
+    /// Any error returned by `r` is propagated,
+    /// and the bytes read up to that point are discarded.
+    pub fn from_reader<R: Read>(r: R) -> io::Result<ByteString> {
+        ByteString::from_reader_with_capacity(r, 0)
+    }
+
+    /// Reads all bytes from `r` until EOF into a new `ByteString`
+    /// whose buffer initially has room for `capacity` bytes.
+    ///
+    /// Any error returned by `r` is propagated,
+    /// and the bytes read up to that point are discarded.
+    pub fn from_reader_with_capacity<R: Read>(mut r: R, capacity: usize) -> io::Result<ByteString> {
+        let mut buf = Vec::with_capacity(capacity);
+        r.read_to_end(&mut buf)?;
+        Ok(ByteString::new(buf))
+    }
+
+    /// Reads the next line from `r` into a new `ByteString`,
+    /// including the terminating `\n` if there is one.
+    ///
+    /// Returns `None` if `r` is already at EOF.
+    pub fn read_line<R: BufRead>(r: &mut R) -> io::Result<Option<ByteString>> {
+        let mut buf = Vec::new();
+        if r.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(ByteString::new(buf)))
+    }
+
+    /// Decodes a UTF-16 encoded slice into a `ByteString` containing its UTF-8 encoding.
+    ///
+    /// Returns an error if `v` contains unpaired surrogates.
+    #[cfg(feature = "text")]
+    pub fn from_utf16(v: &[u16]) -> Result<ByteString, FromUtf16Error> {
+        String::from_utf16(v).map(|s| ByteString::new(s.into_bytes()))
+    }
+
+    /// Decodes a UTF-16 encoded slice into a `ByteString` containing its UTF-8 encoding,
+    /// replacing unpaired surrogates with U+FFFD REPLACEMENT CHARACTER.
+    #[cfg(feature = "text")]
+    pub fn from_utf16_lossy(v: &[u16]) -> ByteString {
+        ByteString::new(String::from_utf16_lossy(v).into_bytes())
+    }
+
+    /// Converts the `ByteString` into its underlying vector of bytes.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Borrows the underlying vector of bytes.
+    pub fn as_vec(&self) -> &Vec<u8> {
+        &self.0
+    }
+
+    /// Compares the bytes of the `ByteString` lexicographically with `other`.
+    pub fn cmp_bytes(&self, other: &[u8]) -> Ordering {
+        self.0[..].cmp(other)
+    }
+
+    /// Returns the number of bytes the `ByteString` can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Shrinks the capacity of the `ByteString` with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length
+    /// and `min_capacity`.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more bytes.
+    ///
+    /// Returns an error instead of aborting if the capacity overflows
+    /// or the allocator reports a failure.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Tries to reserve capacity for exactly `additional` more bytes.
+    ///
+    /// Returns an error instead of aborting if the capacity overflows
+    /// or the allocator reports a failure.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve_exact(additional)
+    }
+
+    /// Sorts the bytes of the `ByteString` in ascending order.
+    ///
+    /// This sort is stable; see `sort_unstable` for a potentially faster alternative.
+    pub fn sort(&mut self) {
+        self.0.sort()
+    }
+
+    /// Sorts the bytes of the `ByteString` in ascending order,
+    /// without preserving the order of equal bytes.
+    pub fn sort_unstable(&mut self) {
+        self.0.sort_unstable()
+    }
+
+    /// Removes consecutive repeated bytes from the `ByteString`.
+    ///
+    /// If the `ByteString` is sorted, this removes all duplicates.
+    pub fn dedup(&mut self) {
+        self.0.dedup()
+    }
+
+    /// Creates a `ByteString` directly from a pointer, a length and a capacity.
+    ///
+    /// # Safety
+    ///
+    /// This has the same requirements as `Vec::from_raw_parts`:
+    /// `ptr` must have been allocated by the global allocator
+    /// with an alignment of 1 and a size of exactly `capacity` bytes,
+    /// `length` must be less than or equal to `capacity`,
+    /// the first `length` bytes must be initialized,
+    /// and ownership of the allocation is transferred to the `ByteString`.
+    /// The easiest way to satisfy these requirements
+    /// is to pass parts obtained from a `Vec<u8>`.
+    pub unsafe fn from_raw_parts(ptr: *mut u8, length: usize, capacity: usize) -> ByteString {
+        ByteString::new(Vec::from_raw_parts(ptr, length, capacity))
+    }
+
+    /// Decomposes the `ByteString` into its raw components:
+    /// a pointer to the bytes, the length and the capacity.
+    ///
+    /// The caller becomes responsible for the memory previously managed by the `ByteString`.
+    /// The only way to release it is to convert the parts back
+    /// with `ByteString::from_raw_parts` (or `Vec::from_raw_parts`).
+    pub fn into_raw_parts(self) -> (*mut u8, usize, usize) {
+        let mut bytes = mem::ManuallyDrop::new(self.0);
+        (bytes.as_mut_ptr(), bytes.len(), bytes.capacity())
+    }
+
+    /// Takes the contents of the `ByteString`, leaving it empty.
+    ///
+    /// The buffer itself is moved to the returned `ByteString`,
+    /// so `self` is left without any allocated capacity.
+    /// Use `take_keeping_capacity` to keep the buffer in `self` instead.
+    pub fn take(&mut self) -> ByteString {
+        mem::take(self)
+    }
+
+    /// Takes the contents of the `ByteString`, leaving it empty
+    /// but retaining its capacity.
+    ///
+    /// The bytes are copied into a new allocation for the returned `ByteString`,
+    /// so that `self` can be reused without reallocating.
+    pub fn take_keeping_capacity(&mut self) -> ByteString {
+        ByteString::new(self.0.drain(..).collect())
+    }
+
+    /// Returns a wrapper whose `Display` implementation outputs the `ByteString` as UTF-8,
+    /// replacing invalid sequences with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Width, fill and alignment are honored as for `str`.
+    /// Unlike `String::from_utf8_lossy`, this only allocates
+    /// when padding or truncating input that is not valid UTF-8.
+    pub fn display_lossy(&self) -> impl Display + '_ {
+        ByteStr::new(&self.0).display_lossy()
+    }
+
+    /// Moves all the bytes of `other` to the end of the `ByteString`,
+    /// leaving `other` empty.
+    pub fn append(&mut self, other: &mut ByteString) {
+        self.0.append(&mut other.0)
+    }
+
+    /// Calls `f` with a mutable reference to the underlying vector of bytes
+    /// and returns its result.
+    pub fn with_bytes<R, F>(&mut self, f: F) -> R
+        where F: FnOnce(&mut Vec<u8>) -> R
+    {
+        f(&mut self.0)
+    }
+
+    /// Reads all bytes from `r` until EOF and appends them to the `ByteString`,
+    /// returning the number of bytes appended.
+    ///
+    /// If `r` returns an error, the error is propagated
+    /// and the bytes read up to that point remain appended.
+    pub fn extend_from_reader<R: Read>(&mut self, mut r: R) -> io::Result<usize> {
+        r.read_to_end(&mut self.0)
+    }
+
+    /// Inserts a byte at position `index`,
+    /// shifting all bytes after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the `ByteString`.
+    pub fn insert_byte(&mut self, index: usize, b: u8) {
+        self.0.insert(index, b)
+    }
+
+    /// Collapses each run of consecutive `b` bytes into a single `b`,
+    /// leaving all other bytes untouched.
+    pub fn dedup_byte(&mut self, b: u8) {
+        self.0.dedup_by(|current, previous| *current == b && *previous == b)
+    }
+
+    /// Removes the last `n` bytes from the `ByteString` and returns them,
+    /// or returns `None` (leaving the `ByteString` unchanged)
+    /// if it contains fewer than `n` bytes.
+    pub fn pop_slice(&mut self, n: usize) -> Option<Vec<u8>> {
+        let at = self.0.len().checked_sub(n)?;
+        Some(self.0.split_off(at))
+    }
+
+    /// Splits the `ByteString` into two at `at`,
+    /// returning the bytes in `[at, len)` and leaving the bytes in `[0, at)` in `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the length of the `ByteString`.
+    pub fn split_off(&mut self, at: usize) -> ByteString {
+        ByteString::new(self.0.split_off(at))
+    }
+
+    /// Inserts the UTF-8 encoding of `s` at byte position `index`,
+    /// shifting all bytes after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the `ByteString`.
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        self.replace_range(index..index, s.as_bytes())
+    }
+
+    /// Replaces the contents of the `ByteString` with `n` copies of its current contents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting length would overflow.
+    pub fn repeat_in_place(&mut self, n: usize) {
+        if n == 0 {
+            self.0.clear();
+            return;
+        }
+
+        let len = self.0.len();
+        let additional = len.checked_mul(n - 1).expect("capacity overflow");
+        self.0.reserve(additional);
+        for _ in 1..n {
+            self.0.extend_from_within(..len);
+        }
+    }
+
+    /// Removes the byte at `index` and returns it,
+    /// replacing it with the last byte of the `ByteString`.
+    ///
+    /// This does not preserve ordering, but is O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove_byte(&mut self, index: usize) -> u8 {
+        self.0.swap_remove(index)
+    }
+
+    /// Appends `fill` bytes until the `ByteString` is `len` bytes long.
+    ///
+    /// Does nothing if the `ByteString` is already at least `len` bytes long.
+    pub fn pad_to(&mut self, len: usize, fill: u8) {
+        if len > self.0.len() {
+            self.0.resize(len, fill);
+        }
+    }
+
+    /// Prepends `fill` bytes until the `ByteString` is `len` bytes long.
+    ///
+    /// Does nothing if the `ByteString` is already at least `len` bytes long.
+    pub fn pad_start_to(&mut self, len: usize, fill: u8) {
+        if len > self.0.len() {
+            let padding = len - self.0.len();
+            self.0.resize(len, fill);
+            self.0.rotate_right(padding);
+        }
+    }
+
+    /// Keeps only the bytes in `range`, removing the bytes before and after it in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of `range` is greater than its end
+    /// or if its end is greater than the length of the `ByteString`.
+    pub fn retain_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.0.len(),
+                "range {:?} out of bounds for length {}", range, self.0.len());
+        self.0.truncate(range.end);
+        self.0.drain(..range.start);
+    }
+
+    /// Removes the first `n` bytes of the `ByteString`,
+    /// shifting the remaining bytes to the start.
+    ///
+    /// If `n` is greater than the length, the `ByteString` is emptied.
+    pub fn shift_left(&mut self, n: usize) {
+        let n = std::cmp::min(n, self.0.len());
+        self.0.drain(..n);
+    }
+
+    /// Inserts `n` `fill` bytes at the start of the `ByteString`,
+    /// shifting the existing bytes to the right.
+    pub fn shift_right(&mut self, n: usize, fill: u8) {
+        let len = self.0.len();
+        self.0.resize(len + n, fill);
+        self.0.rotate_right(n);
+    }
+
+    /// Replaces each run of ASCII whitespace with a single space in place,
+    /// removing leading and trailing whitespace entirely.
+    pub fn collapse_ascii_whitespace(&mut self) {
+        let mut len = 0;
+        let mut pending_space = false;
+        for i in 0..self.0.len() {
+            let b = self.0[i];
+            if b.is_ascii_whitespace() {
+                pending_space = len > 0;
+            } else {
+                if pending_space {
+                    self.0[len] = b' ';
+                    len += 1;
+                    pending_space = false;
+                }
+                self.0[len] = b;
+                len += 1;
+            }
+        }
+        self.0.truncate(len);
+    }
+}
+
+impl Clone for ByteString {
+    fn clone(&self) -> ByteString {
+        ByteString::new(self.0.clone())
+    }
+
+    // Reuses the existing buffer when it is large enough.
+    fn clone_from(&mut self, source: &ByteString) {
+        self.0.clone_from(&source.0)
+    }
+}
+
+impl From<Vec<u8>> for ByteString {
+    fn from(s: Vec<u8>) -> ByteString {
+        ByteString::new(s)
+    }
+}
+
+impl From<&[u8]> for ByteString {
+    fn from(s: &[u8]) -> ByteString {
+        ByteString::new(s.to_vec())
+    }
+}
+
+impl From<u8> for ByteString {
+    fn from(b: u8) -> ByteString {
+        ByteString::new(vec![b])
+    }
+}
+
+impl From<char> for ByteString {
+    fn from(ch: char) -> ByteString {
+        ByteString::new(ch.encode_utf8(&mut [0; 4]).as_bytes().to_vec())
+    }
+}
+
+impl From<ByteString> for Vec<u8> {
+    fn from(s: ByteString) -> Vec<u8> {
+        s.0
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<ByteString> for bytes::Bytes {
+    fn from(s: ByteString) -> bytes::Bytes {
+        bytes::Bytes::from(s.0)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<ByteString> for bytes::BytesMut {
+    fn from(s: ByteString) -> bytes::BytesMut {
+        bytes::BytesMut::from(bytes::Bytes::from(s.0))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for ByteString {
+    fn from(s: bytes::Bytes) -> ByteString {
+        ByteString::new(s.to_vec())
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<const N: usize> From<smallvec::SmallVec<[u8; N]>> for ByteString {
+    fn from(s: smallvec::SmallVec<[u8; N]>) -> ByteString {
+        ByteString::new(s.into_vec())
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<const N: usize> From<ByteString> for smallvec::SmallVec<[u8; N]> {
+    fn from(s: ByteString) -> smallvec::SmallVec<[u8; N]> {
+        smallvec::SmallVec::from_vec(s.0)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl rkyv::Archive for ByteString {
+    type Archived = rkyv::vec::ArchivedVec<u8>;
+    type Resolver = rkyv::vec::VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: rkyv::Place<Self::Archived>) {
+        rkyv::vec::ArchivedVec::resolve_from_slice(&self.0, resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S> rkyv::Serialize<S> for ByteString
+    where S: rkyv::rancor::Fallible + rkyv::ser::Allocator + rkyv::ser::Writer + ?Sized
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        rkyv::vec::ArchivedVec::<u8>::serialize_from_slice(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<D> rkyv::Deserialize<ByteString, D> for rkyv::vec::ArchivedVec<u8>
+    where D: rkyv::rancor::Fallible + ?Sized
+{
+    fn deserialize(&self, _: &mut D) -> Result<ByteString, D::Error> {
+        Ok(ByteString::new(self.as_slice().to_vec()))
+    }
+}
+
+impl From<CString> for ByteString {
+    fn from(s: CString) -> ByteString {
+        ByteString::new(s.into_bytes())
+    }
+}
+
+/// Converts a `ByteString` to a `CString`, appending a NUL terminator.
+///
+/// Fails with a `NulError` if the `ByteString` contains an interior NUL byte.
+/// The original bytes can be recovered with `NulError::into_vec()`.
+impl TryFrom<ByteString> for CString {
+    type Error = NulError;
+
+    fn try_from(s: ByteString) -> Result<CString, NulError> {
+        CString::new(s.0)
+    }
+}
+
+impl<'a> From<&'a ByteString> for &'a ByteStr {
+    fn from(s: &ByteString) -> &ByteStr {
+        ByteStr::new(&s.0)
+    }
+}
+
+impl AsRef<Vec<u8>> for ByteString {
+    fn as_ref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for ByteString {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<Vec<u8>> for ByteString {
+    fn as_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl AsMut<[u8]> for ByteString {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl Borrow<ByteStr> for ByteString {
+    fn borrow(&self) -> &ByteStr {
+        ByteStr::new(&self.0)
+    }
+}
+
+impl Borrow<Vec<u8>> for ByteString {
+    fn borrow(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl Borrow<[u8]> for ByteString {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl BorrowMut<ByteStr> for ByteString {
+    fn borrow_mut(&mut self) -> &mut ByteStr {
+        ByteStr::new_mut(&mut self.0)
+    }
+}
+
+impl BorrowMut<Vec<u8>> for ByteString {
+    fn borrow_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl BorrowMut<[u8]> for ByteString {
+    fn borrow_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl PartialEq<Vec<u8>> for ByteString {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<[u8]> for ByteString {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<ByteString> for Vec<u8> {
+    fn eq(&self, other: &ByteString) -> bool {
+        self == &other.0
+    }
+}
+
+impl PartialEq<ByteString> for [u8] {
+    fn eq(&self, other: &ByteString) -> bool {
+        self == &other.0[..]
+    }
+}
+
+impl<'a> PartialEq<&'a [u8]> for ByteString {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<str> for ByteString {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl<'a> PartialEq<&'a str> for ByteString {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl<'a> PartialEq<Cow<'a, [u8]>> for ByteString {
+    fn eq(&self, other: &Cow<'a, [u8]>) -> bool {
+        self.0 == **other
+    }
+}
+
+impl<'a> PartialOrd<Cow<'a, [u8]>> for ByteString {
+    fn partial_cmp(&self, other: &Cow<'a, [u8]>) -> Option<Ordering> {
+        self.0[..].partial_cmp(&**other)
+    }
+}
+
+impl PartialEq<ByteStr> for ByteString {
+    fn eq(&self, other: &ByteStr) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PartialEq<ByteString> for ByteStr {
+    fn eq(&self, other: &ByteString) -> bool {
+        self.0 == other.0[..]
+    }
+}
+
+impl PartialOrd<ByteStr> for ByteString {
+    fn partial_cmp(&self, other: &ByteStr) -> Option<Ordering> {
+        self.0[..].partial_cmp(&other.0)
+    }
+}
+
+impl PartialOrd<ByteString> for ByteStr {
+    fn partial_cmp(&self, other: &ByteString) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0[..])
+    }
+}
+
+impl<'a> PartialEq<&'a ByteStr> for ByteString {
+    fn eq(&self, other: &&'a ByteStr) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PartialEq<ByteString> for &ByteStr {
+    fn eq(&self, other: &ByteString) -> bool {
+        self.0 == other.0[..]
+    }
+}
+
+impl<'a> PartialOrd<&'a ByteStr> for ByteString {
+    fn partial_cmp(&self, other: &&'a ByteStr) -> Option<Ordering> {
+        self.0[..].partial_cmp(&other.0)
+    }
+}
+
+impl PartialOrd<ByteString> for &ByteStr {
+    fn partial_cmp(&self, other: &ByteString) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0[..])
+    }
+}
+
+impl Deref for ByteString {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for ByteString {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl Default for ByteString {
+    fn default() -> ByteString {
+        ByteString::new(vec![])
+    }
+}
+
+impl FromIterator<u8> for ByteString {
+    fn from_iter<I>(iter: I) -> ByteString
+        where I: IntoIterator<Item=u8>
+    {
+        ByteString::new(Vec::from_iter(iter))
+    }
+}
+
+impl<'a> FromIterator<&'a u8> for ByteString {
+    fn from_iter<I>(iter: I) -> ByteString
+        where I: IntoIterator<Item=&'a u8>
+    {
+        let mut bs = ByteString::default();
+        bs.extend(iter);
+        bs
+    }
+}
+
+impl FromIterator<char> for ByteString {
+    fn from_iter<I>(iter: I) -> ByteString
+        where I: IntoIterator<Item=char>
+    {
+        let mut bs = ByteString::default();
+        bs.extend(iter);
+        bs
+    }
+}
+
+impl<'a> FromIterator<&'a str> for ByteString {
+    fn from_iter<I>(iter: I) -> ByteString
+        where I: IntoIterator<Item=&'a str>
+    {
+        let iter = iter.into_iter();
+        let mut bs = ByteString::default();
+        bs.0.reserve(iter.size_hint().0);
+        bs.extend(iter);
+        bs
+    }
+}
+
+// Extend<u8> and Extend<&u8> are implemented alongside the text impls
+// so that extending with bytes doesn't stop resolving through Deref to Vec<u8>.
+impl Extend<u8> for ByteString {
+    fn extend<I>(&mut self, iter: I)
+        where I: IntoIterator<Item=u8>
+    {
+        self.0.extend(iter)
+    }
+}
+
+impl<'a> Extend<&'a u8> for ByteString {
+    fn extend<I>(&mut self, iter: I)
+        where I: IntoIterator<Item=&'a u8>
+    {
+        self.0.extend(iter)
+    }
+}
+
+impl Extend<char> for ByteString {
+    fn extend<I>(&mut self, iter: I)
+        where I: IntoIterator<Item=char>
+    {
+        let iter = iter.into_iter();
+        self.0.reserve(iter.size_hint().0);
+        let mut buf = [0; 4];
+        for ch in iter {
+            self.0.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for ByteString {
+    fn extend<I>(&mut self, iter: I)
+        where I: IntoIterator<Item=&'a str>
+    {
+        for s in iter {
+            self.0.extend_from_slice(s.as_bytes());
+        }
+    }
+}
+
+impl IntoIterator for ByteString {
+    type Item = u8;
+    type IntoIter = std::vec::IntoIter<u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ByteString {
+    type Item = &'a u8;
+    type IntoIter = std::slice::Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut ByteString {
+    type Item = &'a mut u8;
+    type IntoIter = std::slice::IterMut<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl Debug for ByteString {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        // Delegate to ByteStr's implementation
+        Debug::fmt(Borrow::<ByteStr>::borrow(self), f)
+    }
+}
+
+impl LowerHex for ByteString {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        LowerHex::fmt(Borrow::<ByteStr>::borrow(self), f)
+    }
+}
+
+impl UpperHex for ByteString {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        UpperHex::fmt(Borrow::<ByteStr>::borrow(self), f)
+    }
+}
+
+/// The `proptest` strategy returned by `bytes_strategy()`.
+#[cfg(feature = "proptest")]
+pub type BytesStrategy = proptest::strategy::Map<
+    proptest::collection::VecStrategy<proptest::num::u8::Any>,
+    fn(Vec<u8>) -> ByteString,
+>;
+
+/// Returns a `proptest` strategy that generates `ByteString`s
+/// whose length is within `len_range`.
+#[cfg(feature = "proptest")]
+pub fn bytes_strategy<R>(len_range: R) -> BytesStrategy
+    where R: Into<proptest::collection::SizeRange>
+{
+    use proptest::prelude::*;
+
+    prop::collection::vec(any::<u8>(), len_range).prop_map(ByteString::new as fn(Vec<u8>) -> ByteString)
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for ByteString {
+    type Parameters = proptest::collection::SizeRange;
+    type Strategy = BytesStrategy;
+
+    fn arbitrary_with(len_range: Self::Parameters) -> Self::Strategy {
+        bytes_strategy(len_range)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes)]
+mod tests {
+    use super::*;
+
+    const EMPTY: &'static str = "b\"\"";
+    const ALL_BYTES: &'static str = concat!("b\"",
+        "\\x00\\x01\\x02\\x03\\x04\\x05\\x06\\x07\\x08\\t\\n\\x0b\\x0c\\r\\x0e\\x0f",
+        "\\x10\\x11\\x12\\x13\\x14\\x15\\x16\\x17\\x18\\x19\\x1a\\x1b\\x1c\\x1d\\x1e\\x1f",
+        " !\\\"#$%&\\\'()*+,-./",
+        "0123456789:;<=>?",
+        "@ABCDEFGHIJKLMNO",
+        "PQRSTUVWXYZ[\\\\]^_",
+        "`abcdefghijklmno",
+        "pqrstuvwxyz{|}~\\x7f",
+        "\\x80\\x81\\x82\\x83\\x84\\x85\\x86\\x87\\x88\\x89\\x8a\\x8b\\x8c\\x8d\\x8e\\x8f",
+        "\\x90\\x91\\x92\\x93\\x94\\x95\\x96\\x97\\x98\\x99\\x9a\\x9b\\x9c\\x9d\\x9e\\x9f",
+        "\\xa0\\xa1\\xa2\\xa3\\xa4\\xa5\\xa6\\xa7\\xa8\\xa9\\xaa\\xab\\xac\\xad\\xae\\xaf",
+        "\\xb0\\xb1\\xb2\\xb3\\xb4\\xb5\\xb6\\xb7\\xb8\\xb9\\xba\\xbb\\xbc\\xbd\\xbe\\xbf",
+        "\\xc0\\xc1\\xc2\\xc3\\xc4\\xc5\\xc6\\xc7\\xc8\\xc9\\xca\\xcb\\xcc\\xcd\\xce\\xcf",
+        "\\xd0\\xd1\\xd2\\xd3\\xd4\\xd5\\xd6\\xd7\\xd8\\xd9\\xda\\xdb\\xdc\\xdd\\xde\\xdf",
+        "\\xe0\\xe1\\xe2\\xe3\\xe4\\xe5\\xe6\\xe7\\xe8\\xe9\\xea\\xeb\\xec\\xed\\xee\\xef",
+        "\\xf0\\xf1\\xf2\\xf3\\xf4\\xf5\\xf6\\xf7\\xf8\\xf9\\xfa\\xfb\\xfc\\xfd\\xfe\\xff",
+        "\"");
+
+    #[test]
+    fn debug_bytestr_empty() {
+        let bytes = [];
+        let bs = ByteStr::new(&bytes);
+        let result = format!("{:?}", bs);
+        assert_eq!(result, EMPTY);
+    }
+
+    #[test]
+    fn debug_bytestr() {
+        let bytes = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F,
+            0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x2B, 0x2C, 0x2D, 0x2E, 0x2F,
+            0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F,
+            0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E, 0x5F,
+            0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F,
+            0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x7B, 0x7C, 0x7D, 0x7E, 0x7F,
+            0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x8D, 0x8E, 0x8F,
+            0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B, 0x9C, 0x9D, 0x9E, 0x9F,
+            0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xAE, 0xAF,
+            0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF,
+            0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE, 0xCF,
+            0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xDB, 0xDC, 0xDD, 0xDE, 0xDF,
+            0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xEB, 0xEC, 0xED, 0xEE, 0xEF,
+            0xF0, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0xFE, 0xFF,
+        ];
+        let bs = ByteStr::new(&bytes);
+        let result = format!("{:?}", bs);
+        assert_eq!(result, ALL_BYTES);
+    }
+
+    #[test]
+    fn debug_bytestring_empty() {
+        let bytes = vec![];
+        let bs = ByteString::new(bytes);
+        let result = format!("{:?}", bs);
+        assert_eq!(result, EMPTY);
+    }
+
+    #[test]
+    fn debug_bytestring() {
+        let bytes = vec![
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F,
+            0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x2B, 0x2C, 0x2D, 0x2E, 0x2F,
+            0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F,
+            0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E, 0x5F,
+            0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F,
+            0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x7B, 0x7C, 0x7D, 0x7E, 0x7F,
+            0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x8D, 0x8E, 0x8F,
+            0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B, 0x9C, 0x9D, 0x9E, 0x9F,
+            0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xAE, 0xAF,
+            0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF,
+            0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE, 0xCF,
+            0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xDB, 0xDC, 0xDD, 0xDE, 0xDF,
+            0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xEB, 0xEC, 0xED, 0xEE, 0xEF,
+            0xF0, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0xFE, 0xFF,
+        ];
+        let bs = ByteString::new(bytes);
+        let result = format!("{:?}", bs);
+        assert_eq!(result, ALL_BYTES);
+    }
+
+    #[test]
+    fn bytestring_leak() {
+        // Leaking a few bytes once is acceptable in a test.
+        let leaked: &'static mut ByteStr = ByteString::new(b"leak".to_vec()).leak();
+        assert_eq!(&leaked.0, b"leak");
+        leaked[0] = b'p';
+        assert_eq!(&leaked.0, b"peak");
+    }
+
+    #[test]
+    fn bytestr_rchunks() {
+        let bs = ByteStr::new(b"abcde");
+        let chunks: Vec<&ByteStr> = bs.rchunks(2).collect();
+        assert_eq!(chunks, [ByteStr::new(b"de"), ByteStr::new(b"bc"), ByteStr::new(b"a")]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestr_rchunks_zero_size() {
+        ByteStr::new(b"abcde").rchunks(0);
+    }
+
+    #[test]
+    fn bytestr_escape_html() {
+        let bs = ByteStr::new(b"<a href=\"x\">&'\xff</a>");
+        assert_eq!(bs.escape_html(), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;\u{FFFD}&lt;/a&gt;");
+    }
+
+    #[test]
+    fn bytestr_read_integers() {
+        let bs = ByteStr::new(b"\x01\x02\x03\x04\x05\x06\x07\x08\x09");
+        assert_eq!(bs.read_u16_be(0), Some(0x0102));
+        assert_eq!(bs.read_u16_le(7), Some(0x0908));
+        assert_eq!(bs.read_u32_be(1), Some(0x02030405));
+        assert_eq!(bs.read_u32_le(5), Some(0x09080706));
+        assert_eq!(bs.read_u64_be(1), Some(0x0203040506070809));
+        assert_eq!(bs.read_u64_le(0), Some(0x0807060504030201));
+    }
+
+    #[test]
+    fn bytestr_read_integers_out_of_bounds() {
+        let bs = ByteStr::new(b"\x01\x02\x03\x04\x05\x06\x07\x08\x09");
+        assert_eq!(bs.read_u16_be(8), None);
+        assert_eq!(bs.read_u32_le(6), None);
+        assert_eq!(bs.read_u64_be(2), None);
+        assert_eq!(bs.read_u64_le(usize::MAX), None);
+    }
+
+    #[test]
+    fn bytestr_is_ascii() {
+        assert!(ByteStr::new(b"plain ASCII\x7f").is_ascii());
+        assert!(!ByteStr::new(b"not \x80 ASCII").is_ascii());
+    }
+
+    #[test]
+    fn bytestring_into_ascii() {
+        let ascii = ByteString::new(b"ascii".to_vec());
+        assert_eq!(ascii.into_ascii(), Ok(ByteString::new(b"ascii".to_vec())));
+
+        let high = ByteString::new(b"high \x80".to_vec());
+        assert_eq!(high.into_ascii(), Err(ByteString::new(b"high \x80".to_vec())));
+    }
+
+    #[test]
+    fn bytestring_from_slice() {
+        let mut bytes = *b"abc";
+        let bs: ByteString = (&bytes[..]).into();
+        bytes[0] = b'x';
+        assert_eq!(bs, b"abc"[..]);
+        assert_eq!(bytes, *b"xbc");
+    }
+
+    #[test]
+    fn bytestr_cmp_bytestring() {
+        let bs = ByteStr::new(b"abc");
+        assert!(*bs < ByteString::new(b"abd".to_vec()));
+        assert!(*bs == ByteString::new(b"abc".to_vec()));
+        assert!(*bs > ByteString::new(b"ab".to_vec()));
+        assert!(ByteString::new(b"ab".to_vec()) < *bs);
+        assert_eq!(bs.partial_cmp(&ByteString::new(b"abc".to_vec())), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn bytestring_replace_range() {
+        let mut bs = ByteString::new(b"hello world".to_vec());
+        bs.replace_range(0..5, b"goodbye");
+        assert_eq!(bs, b"goodbye world"[..]);
+        bs.replace_range(8..13, b"all");
+        assert_eq!(bs, b"goodbye all"[..]);
+        bs.replace_range(4..8, b"");
+        assert_eq!(bs, b"goodall"[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestring_replace_range_out_of_bounds() {
+        let mut bs = ByteString::new(b"abc".to_vec());
+        bs.replace_range(2..4, b"x");
+    }
+
+    #[test]
+    fn bytestring_from_reader() {
+        let bs = ByteString::from_reader(&b"from a reader"[..]).unwrap();
+        assert_eq!(bs, b"from a reader"[..]);
+
+        let bs = ByteString::from_reader_with_capacity(&b"abc"[..], 64).unwrap();
+        assert_eq!(bs, b"abc"[..]);
+        assert!(bs.capacity() >= 64);
+    }
+
+    #[test]
+    fn bytestring_from_reader_error() {
+        struct FailingReader(bool);
+
+        impl Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 {
+                    Err(io::Error::other("mid-stream failure"))
+                } else {
+                    self.0 = true;
+                    buf[0] = b'a';
+                    Ok(1)
+                }
+            }
+        }
+
+        let err = ByteString::from_reader(FailingReader(false)).unwrap_err();
+        assert_eq!(err.to_string(), "mid-stream failure");
+    }
+
+    #[test]
+    fn bytestring_extend_chars() {
+        let mut bs = ByteString::new(b"x".to_vec());
+        bs.extend("a\u{e9}b".chars());
+        assert_eq!(bs, b"xa\xc3\xa9b"[..]);
+    }
+
+    #[test]
+    fn bytestring_extend_strs() {
+        let mut bs = ByteString::default();
+        bs.extend(vec!["ab", "", "\u{2603}"]);
+        assert_eq!(bs, b"ab\xe2\x98\x83"[..]);
+        bs.extend(b"cd");
+        assert_eq!(bs, b"ab\xe2\x98\x83cd"[..]);
+    }
+
+    #[test]
+    fn cstring_from_bytestring() {
+        let bs = ByteString::new(b"hello".to_vec());
+        let cs = CString::try_from(bs).unwrap();
+        assert_eq!(cs.as_bytes_with_nul(), b"hello\0");
+        assert_eq!(ByteString::from(cs), b"hello"[..]);
+    }
+
+    #[test]
+    fn cstring_from_bytestring_interior_nul() {
+        let bs = ByteString::new(b"hel\0lo".to_vec());
+        let err = CString::try_from(bs).unwrap_err();
+        assert_eq!(err.nul_position(), 3);
+        assert_eq!(err.into_vec(), b"hel\0lo");
+    }
+
+    #[test]
+    fn bytestr_split_at_checked() {
+        let bs = ByteStr::new(b"abcd");
+        assert_eq!(bs.split_at_checked(1), Some((ByteStr::new(b"a"), ByteStr::new(b"bcd"))));
+        assert_eq!(bs.split_at_checked(4), Some((ByteStr::new(b"abcd"), ByteStr::new(b""))));
+        assert_eq!(bs.split_at_checked(5), None);
+    }
+
+    #[test]
+    fn bytestr_eq_references() {
+        let bs = ByteStr::new(b"abc");
+        assert_eq!(*bs, &b"abc"[..]);
+        assert_ne!(*bs, &b"abd"[..]);
+        assert!(*bs == "abc");
+        assert!(*bs == *"abc");
+    }
+
+    #[test]
+    fn bytestring_eq_references() {
+        let bs = ByteString::new(b"abc".to_vec());
+        assert_eq!(bs, &b"abc"[..]);
+        assert_ne!(bs, &b"ab"[..]);
+        assert!(bs == "abc");
+        assert!(bs != "abd");
+        assert!(bs == *"abc");
+    }
+
+    #[test]
+    #[cfg(feature = "text")]
+    fn bytestring_from_utf16() {
+        let bs = ByteString::from_utf16(&[0x0041, 0x00E9, 0x2603]).unwrap();
+        assert_eq!(bs, "A\u{e9}\u{2603}");
+        assert!(ByteString::from_utf16(&[0x0041, 0xD800]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "text")]
+    fn bytestring_from_utf16_lossy() {
+        let bs = ByteString::from_utf16_lossy(&[0x0041, 0xD800, 0x2603]);
+        assert_eq!(bs, "A\u{fffd}\u{2603}");
+    }
+
+    #[test]
+    fn bytestr_escape_into() {
+        let bs = ByteStr::new(b"\0tab\there \"quoted\" \\ \xff");
+        let mut out = b"prefix ".to_vec();
+        bs.escape_into(&mut out);
+        assert_eq!(out, format!("prefix {:?}", bs).into_bytes());
+    }
+
+    #[test]
+    fn bytestr_index() {
+        let bs = ByteStr::new(b"abcde");
+        let byte: u8 = bs[1];
+        assert_eq!(byte, b'b');
+        let range: &ByteStr = &bs[1..3];
+        assert_eq!(range, ByteStr::new(b"bc"));
+        let range_from: &ByteStr = &bs[3..];
+        assert_eq!(range_from, ByteStr::new(b"de"));
+        let range_to: &ByteStr = &bs[..2];
+        assert_eq!(range_to, ByteStr::new(b"ab"));
+        let range_full: &ByteStr = bs.index(..);
+        assert_eq!(range_full, bs);
+    }
+
+    #[test]
+    fn bytestr_index_mut() {
+        let mut bytes = *b"abcde";
+        let bs = ByteStr::new_mut(&mut bytes);
+        bs[0] = b'A';
+        bs[3..].copy_from_slice(b"DE");
+        assert_eq!(bytes, *b"AbcDE");
+    }
+
+    #[test]
+    fn bytestring_into_vec() {
+        let bytes = b"abc".to_vec();
+        let ptr = bytes.as_ptr();
+        let bs = ByteString::new(bytes);
+        assert_eq!(bs.as_vec(), &b"abc".to_vec());
+        assert_eq!(bs.as_vec().as_ptr(), ptr);
+        let bytes = bs.into_vec();
+        assert_eq!(bytes, b"abc");
+        assert_eq!(bytes.as_ptr(), ptr);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn bytestring_arbitrary_roundtrip(bs in proptest::prelude::any::<ByteString>()) {
+            let bytes = Vec::from(bs.clone());
+            proptest::prop_assert_eq!(ByteString::from(bytes), bs);
+        }
+
+        #[test]
+        fn bytes_strategy_length(bs in bytes_strategy(2..5)) {
+            proptest::prop_assert!(bs.len() >= 2 && bs.len() < 5);
+        }
+    }
+
+    #[test]
+    fn bytestr_cmp_bytes() {
+        let bs = ByteStr::new(b"abc");
+        assert_eq!(bs.cmp_bytes(b"abd"), Ordering::Less);
+        assert_eq!(bs.cmp_bytes(b"abcd"), Ordering::Less);
+        assert_eq!(bs.cmp_bytes(b"abc"), Ordering::Equal);
+        assert_eq!(bs.cmp_bytes(b"abb"), Ordering::Greater);
+        assert_eq!(bs.cmp_bytes(b"ab"), Ordering::Greater);
+    }
+
+    #[test]
+    fn bytestring_cmp_bytes() {
+        let bs = ByteString::new(b"abc".to_vec());
+        assert_eq!(bs.cmp_bytes(b"b"), Ordering::Less);
+        assert_eq!(bs.cmp_bytes(b"abc"), Ordering::Equal);
+        assert_eq!(bs.cmp_bytes(b""), Ordering::Greater);
+    }
+
+    #[test]
+    fn bytestr_cloned() {
+        let mut bytes = *b"abc";
+        let bs = ByteStr::new_mut(&mut bytes).cloned();
+        bytes[0] = b'x';
+        assert_eq!(bs, b"abc"[..]);
+        assert_eq!(bytes, *b"xbc");
+    }
+
+    #[test]
+    fn bytestr_find_byte_from() {
+        let bs = ByteStr::new(b"a,b,,c");
+        let mut found = vec![];
+        let mut start = 0;
+        while let Some(i) = bs.find_byte_from(b',', start) {
+            found.push(i);
+            start = i + 1;
+        }
+        assert_eq!(found, [1, 3, 4]);
+        assert_eq!(bs.find_byte_from(b',', 6), None);
+        assert_eq!(bs.find_byte_from(b',', 100), None);
+    }
+
+    #[test]
+    fn bytestr_rfind_byte_before() {
+        let bs = ByteStr::new(b"a,b,,c");
+        let mut found = vec![];
+        let mut end = bs.len();
+        while let Some(i) = bs.rfind_byte_before(b',', end) {
+            found.push(i);
+            end = i;
+        }
+        assert_eq!(found, [4, 3, 1]);
+        assert_eq!(bs.rfind_byte_before(b',', 100), Some(4));
+        assert_eq!(bs.rfind_byte_before(b',', 1), None);
+    }
+
+    #[test]
+    fn bytestring_shrink_to() {
+        let mut bs = ByteString::new(Vec::with_capacity(100));
+        bs.extend(b"abcd");
+        assert!(bs.capacity() >= 100);
+        bs.shrink_to(10);
+        assert!(bs.capacity() >= 10 && bs.capacity() < 100);
+        bs.shrink_to(bs.len());
+        assert!(bs.capacity() >= 4 && bs.capacity() < 10);
+        assert_eq!(bs, b"abcd"[..]);
+    }
+
+    #[test]
+    fn bytestr_add() {
+        let a = ByteStr::new(b"foo");
+        let b = ByteStr::new(b"bar");
+        assert_eq!(a + b, b"foobar"[..]);
+        assert_eq!(ByteStr::new(b"") + b, b"bar"[..]);
+        assert_eq!(a + ByteStr::new(b""), b"foo"[..]);
+    }
+
+    #[test]
+    fn bytestr_match_indices() {
+        let bs = ByteStr::new(b"abcabc");
+        let found: Vec<_> = bs.match_indices(b"abc").collect();
+        assert_eq!(found, [(0, ByteStr::new(b"abc")), (3, ByteStr::new(b"abc"))]);
+        assert_eq!(bs.match_indices(b"xyz").next(), None);
+
+        let found: Vec<_> = ByteStr::new(b"aaaa").match_indices(b"aa").map(|(i, _)| i).collect();
+        assert_eq!(found, [0, 2]);
+    }
+
+    #[test]
+    fn bytestr_matches() {
+        let bs = ByteStr::new(b"abcabc");
+        assert_eq!(bs.matches(b"abc").count(), 2);
+        assert_eq!(bs.matches(b"abcd").count(), 0);
+        assert_eq!(bs.matches(b"").count(), 7);
+    }
+
+    #[test]
+    fn bytestring_sort_dedup() {
+        let mut bs = ByteString::new(vec![3, 1, 2, 2, 1]);
+        bs.sort();
+        bs.dedup();
+        assert_eq!(bs, [1, 2, 3][..]);
+
+        let mut bs = ByteString::new(vec![3, 1, 2, 2, 1]);
+        bs.sort_unstable();
+        assert_eq!(bs, [1, 1, 2, 2, 3][..]);
+    }
+
+    #[test]
+    fn bytestr_debug_utf8_aware() {
+        let bs = ByteStr::new("caf\u{e9} \u{2603}\n\"".as_bytes());
+        assert_eq!(format!("{:?}", bs.debug_utf8_aware()), "b\"caf\u{e9} \u{2603}\\n\\\"\"");
+    }
+
+    #[test]
+    fn bytestr_debug_utf8_aware_invalid() {
+        let bs = ByteStr::new(b"caf\xc3\xa9\xff\xc2\x85");
+        assert_eq!(format!("{:?}", bs.debug_utf8_aware()), "b\"caf\u{e9}\\xff\\xc2\\x85\"");
+    }
+
+    #[test]
+    fn box_bytestr_conversions() {
+        let boxed: Box<[u8]> = b"boxed".to_vec().into_boxed_slice();
+        let ptr = boxed.as_ptr();
+
+        let bs: Box<ByteStr> = boxed.into();
+        assert_eq!(&bs.0, b"boxed");
+        assert_eq!(bs.as_ptr(), ptr);
+
+        let boxed: Box<[u8]> = bs.into();
+        assert_eq!(&*boxed, b"boxed");
+        assert_eq!(boxed.as_ptr(), ptr);
+
+        let bs: Box<ByteStr> = boxed.into();
+        let vec: Vec<u8> = bs.into();
+        assert_eq!(vec, b"boxed");
+        assert_eq!(vec.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn bytestr_utf8_chunks() {
+        let bs = ByteStr::new(b"caf\xc3\xa9\xff\xfetail");
+        let chunks: Vec<_> = bs.utf8_chunks().map(|c| (c.valid(), c.invalid())).collect();
+        assert_eq!(chunks, [
+            ("caf\u{e9}", ByteStr::new(b"\xff")),
+            ("", ByteStr::new(b"\xfe")),
+            ("tail", ByteStr::new(b"")),
+        ]);
+    }
+
+    #[test]
+    fn bytestring_cmp_bytestr_ref() {
+        let owned = ByteString::from(b"abc".to_vec());
+        let borrowed = ByteStr::new(b"abc");
+        assert!(owned == borrowed);
+        assert!(borrowed == owned);
+        assert!(owned != ByteStr::new(b"abd"));
+        assert!(owned < ByteStr::new(b"abd"));
+        assert!(ByteStr::new(b"abd") > owned);
+        assert_eq!(owned.partial_cmp(&borrowed), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn bytestring_from_raw_parts() {
+        let mut bytes = mem::ManuallyDrop::new(b"raw parts".to_vec());
+        let (ptr, length, capacity) = (bytes.as_mut_ptr(), bytes.len(), bytes.capacity());
+        let bs = unsafe { ByteString::from_raw_parts(ptr, length, capacity) };
+        assert_eq!(bs, b"raw parts"[..]);
+        assert_eq!(bs.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn bytestring_raw_parts_roundtrip() {
+        let mut bs = ByteString::new(Vec::with_capacity(16));
+        bs.extend(b"round trip");
+        let (ptr, length, capacity) = bs.into_raw_parts();
+        assert_eq!(length, 10);
+        assert!(capacity >= 16);
+        let bs = unsafe { ByteString::from_raw_parts(ptr, length, capacity) };
+        assert_eq!(bs, b"round trip"[..]);
+        assert_eq!(bs.capacity(), capacity);
+    }
+
+    #[test]
+    fn bytestr_reversed() {
+        let bs = ByteStr::new(b"abc");
+        assert_eq!(bs.reversed(), b"cba"[..]);
+        assert_eq!(bs, ByteStr::new(b"abc"));
+    }
+
+    #[test]
+    fn bytestr_reverse() {
+        let mut bytes = *b"abc";
+        ByteStr::new_mut(&mut bytes).reverse();
+        assert_eq!(bytes, *b"cba");
+    }
+
+    #[test]
+    fn bytestring_take() {
+        let mut bs = ByteString::new(b"taken".to_vec());
+        let taken = bs.take();
+        assert_eq!(taken, b"taken"[..]);
+        assert!(bs.is_empty());
+        assert_eq!(bs.capacity(), 0);
+    }
+
+    #[test]
+    fn bytestring_take_keeping_capacity() {
+        let mut bs = ByteString::new(Vec::with_capacity(32));
+        bs.extend(b"taken");
+        let taken = bs.take_keeping_capacity();
+        assert_eq!(taken, b"taken"[..]);
+        assert!(bs.is_empty());
+        assert!(bs.capacity() >= 32);
+    }
+
+    #[test]
+    fn bytestr_split_ascii_whitespace() {
+        let tokens: Vec<_> = ByteStr::new(b"a  bc\t\r\nd").split_ascii_whitespace().collect();
+        assert_eq!(tokens, [ByteStr::new(b"a"), ByteStr::new(b"bc"), ByteStr::new(b"d")]);
+
+        let tokens: Vec<_> = ByteStr::new(b"  lead trail \n").split_ascii_whitespace().collect();
+        assert_eq!(tokens, [ByteStr::new(b"lead"), ByteStr::new(b"trail")]);
+
+        assert_eq!(ByteStr::new(b" \t \n ").split_ascii_whitespace().next(), None);
+        assert_eq!(ByteStr::new(b"").split_ascii_whitespace().next(), None);
+    }
+
+    #[test]
+    fn hash_borrow_consistency() {
+        use std::collections::HashMap;
+
+        let key = ByteStr::new(b"key");
+
+        let mut boxed: HashMap<Box<ByteStr>, i32> = HashMap::new();
+        boxed.insert(b"key".to_vec().into_boxed_slice().into(), 1);
+        assert_eq!(boxed.get(key), Some(&1));
+
+        let mut owned: HashMap<ByteString, i32> = HashMap::new();
+        owned.insert(ByteString::new(b"key".to_vec()), 2);
+        assert_eq!(owned.get(key), Some(&2));
+        assert_eq!(owned.get(ByteStr::new(b"other")), None);
+    }
+
+    #[test]
+    fn bytestr_display_lossy() {
+        let bs = ByteStr::new("caf\u{e9}".as_bytes());
+        assert_eq!(bs.display_lossy().to_string(), "caf\u{e9}");
+
+        let bs = ByteStr::new(b"a\xffb\xf0\x9f\x98c");
+        assert_eq!(bs.display_lossy().to_string(), String::from_utf8_lossy(&bs.0));
+        assert_eq!(bs.display_lossy().to_string(), "a\u{fffd}b\u{fffd}c");
+        assert_eq!(format!("[{:>7}]", bs.display_lossy()), "[  a\u{fffd}b\u{fffd}c]");
+
+        assert_eq!(format!("[{:>6}]", ByteStr::new(b"ab").display_lossy()), "[    ab]");
+        assert_eq!(format!("[{:-<4.1}]", ByteStr::new(b"ab").display_lossy()), "[a---]");
+    }
+
+    #[test]
+    fn bytestring_display_lossy() {
+        let bs = ByteString::new(b"ok\x80".to_vec());
+        assert_eq!(format!("[{}]", bs.display_lossy()), "[ok\u{fffd}]");
+    }
+
+    #[test]
+    fn bytestring_append() {
+        let mut bs = ByteString::new(b"foo".to_vec());
+        let mut other = ByteString::new(b"bar".to_vec());
+        bs.append(&mut other);
+        assert_eq!(bs, b"foobar"[..]);
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn bytestr_as_chunks() {
+        let (chunks, remainder) = ByteStr::new(b"abcdef").as_chunks::<3>();
+        assert_eq!(chunks, [*b"abc", *b"def"]);
+        assert_eq!(remainder, ByteStr::new(b""));
+
+        let (chunks, remainder) = ByteStr::new(b"abcdefg").as_chunks::<2>();
+        assert_eq!(chunks, [*b"ab", *b"cd", *b"ef"]);
+        assert_eq!(remainder, ByteStr::new(b"g"));
+    }
+
+    #[test]
+    fn debug_bytestr_large_ascii() {
+        let bytes: Vec<u8> = (0..100_000).map(|i| b"lorem ipsum \"dolor\" sit\\amet\n"[i % 29]).collect();
+        let mut expected = String::from("b\"");
+        for &byte in &bytes {
+            for ch in std::ascii::escape_default(byte) {
+                expected.push(ch as char);
+            }
+        }
+        expected.push('"');
+        assert_eq!(format!("{:?}", ByteStr::new(&bytes)), expected);
+    }
+
+    #[test]
+    fn bytestring_with_bytes() {
+        let mut bs = ByteString::new(b"ab".to_vec());
+        let len = bs.with_bytes(|bytes| {
+            bytes.push(b'c');
+            bytes.len()
+        });
+        assert_eq!(len, 3);
+        assert_eq!(bs, b"abc"[..]);
+    }
+
+    #[test]
+    fn bytestr_eq_bytes() {
+        let bs = ByteStr::new(b"abc");
+        assert!(bs.eq_bytes(&b"abc"[..]));
+        assert!(bs.eq_bytes(b"abc"));
+        assert!(bs.eq_bytes(*b"abc"));
+        let vec: Vec<u8> = b"abc".to_vec();
+        assert!(bs.eq_bytes(vec));
+        assert!(bs.eq_bytes(ByteStr::new(b"abc")));
+        assert!(bs.eq_bytes(ByteString::new(b"abc".to_vec())));
+        assert!(bs.eq_bytes("abc"));
+        assert!(!bs.eq_bytes(b"abd"));
+        assert!(!bs.eq_bytes(b""));
+    }
+
+    #[test]
+    fn vec_borrow_bytestr() {
+        use std::collections::HashMap;
+
+        let mut map: HashMap<Vec<u8>, i32> = HashMap::new();
+        map.insert(b"key".to_vec(), 1);
+        assert_eq!(map.get(ByteStr::new(b"key")), Some(&1));
+        assert_eq!(map.get(ByteStr::new(b"other")), None);
+    }
+
+    #[test]
+    fn bytestr_trim_matches_byte() {
+        assert_eq!(ByteStr::new(b"data\0\0\0").trim_end_matches_byte(0), ByteStr::new(b"data"));
+        assert_eq!(ByteStr::new(b"00042").trim_start_matches_byte(b'0'), ByteStr::new(b"42"));
+        assert_eq!(ByteStr::new(b"--a-b--").trim_matches_byte(b'-'), ByteStr::new(b"a-b"));
+        assert_eq!(ByteStr::new(b"\0\0\0").trim_matches_byte(0), ByteStr::new(b""));
+        assert_eq!(ByteStr::new(b"\0\0\0").trim_start_matches_byte(0), ByteStr::new(b""));
+        assert_eq!(ByteStr::new(b"\0\0\0").trim_end_matches_byte(0), ByteStr::new(b""));
+    }
+
+    #[test]
+    fn bytestring_collect_results() {
+        let ok: Result<ByteString, &str> = vec![Ok(b'a'), Ok(b'b')].into_iter().collect();
+        assert_eq!(ok, Ok(ByteString::new(b"ab".to_vec())));
+
+        let mut consumed = 0;
+        let err: Result<ByteString, &str> = vec![Ok(b'a'), Err("bad byte"), Ok(b'b')]
+            .into_iter()
+            .inspect(|_| consumed += 1)
+            .collect();
+        assert_eq!(err, Err("bad byte"));
+        assert_eq!(consumed, 2);
+    }
+
+    #[test]
+    fn bytestr_try_slice() {
+        let bs = ByteStr::new(b"abcdef");
+        assert_eq!(bs.try_slice(1..4), Ok(ByteStr::new(b"bcd")));
+        assert_eq!(bs.try_slice(6..6), Ok(ByteStr::new(b"")));
+
+        let err = bs.try_slice(2..7).unwrap_err();
+        assert_eq!(err, RangeError { start: 2, end: 7, len: 6 });
+        assert_eq!(err.to_string(), "range end 7 is out of bounds for length 6");
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let err = bs.try_slice(4..2).unwrap_err();
+        assert_eq!((err.start, err.end, err.len), (4, 2, 6));
+        assert_eq!(err.to_string(), "range start 4 is greater than range end 2");
+    }
+
+    #[test]
+    #[cfg(feature = "smallvec")]
+    fn bytestring_smallvec_roundtrip() {
+        let small: smallvec::SmallVec<[u8; 8]> = smallvec::SmallVec::from_slice(b"inline");
+        assert!(!small.spilled());
+        let bs = ByteString::from(small);
+        assert_eq!(bs, b"inline"[..]);
+
+        let small: smallvec::SmallVec<[u8; 8]> = bs.into();
+        assert_eq!(&small[..], b"inline");
+    }
+
+    #[test]
+    fn bytestr_split_once_str() {
+        let bs = ByteStr::new("a\u{2192}b\u{2192}c".as_bytes());
+        assert_eq!(bs.split_once_str("\u{2192}"), Some((ByteStr::new(b"a"), ByteStr::new("b\u{2192}c".as_bytes()))));
+        assert_eq!(bs.rsplit_once_str("\u{2192}"), Some((ByteStr::new("a\u{2192}b".as_bytes()), ByteStr::new(b"c"))));
+        assert_eq!(bs.split_once_str("::"), None);
+        assert_eq!(bs.rsplit_once_str("::"), None);
+    }
+
+    #[test]
+    fn bytestring_extend_from_reader() {
+        let mut bs = ByteString::new(b"head ".to_vec());
+        assert_eq!(bs.extend_from_reader(io::Cursor::new(b"first ")).unwrap(), 6);
+        assert_eq!(bs.extend_from_reader(&b"second"[..]).unwrap(), 6);
+        assert_eq!(bs.extend_from_reader(io::empty()).unwrap(), 0);
+        assert_eq!(bs, b"head first second"[..]);
+    }
+
+    #[test]
+    fn bytestr_split_header() {
+        assert_eq!(ByteStr::new(b"\x01\x02").split_header::<2>(), Some((b"\x01\x02", ByteStr::new(b""))));
+        assert_eq!(ByteStr::new(b"\x01").split_header::<2>(), None);
+        assert_eq!(
+            ByteStr::new(b"\x01\x02body").split_header::<2>(),
+            Some((b"\x01\x02", ByteStr::new(b"body")))
+        );
+    }
+
+    #[test]
+    fn default_mut_bytestr_lifetime() {
+        fn make_default() -> &'static mut ByteStr {
+            Default::default()
+        }
+
+        #[derive(Default)]
+        struct Holder<'a> {
+            bytes: &'a mut ByteStr,
+        }
+
+        let first = make_default();
+        let second = make_default();
+        assert!(first.is_empty() && second.is_empty());
+
+        let holder: Holder<'static> = Holder::default();
+        assert_eq!(holder.bytes, ByteStr::new(b""));
+    }
+
+    #[test]
+    fn bytestr_copy_from_slice() {
+        let mut bytes = [0; 3];
+        ByteStr::new_mut(&mut bytes).copy_from_slice(b"abc");
+        assert_eq!(bytes, *b"abc");
+        ByteStr::new_mut(&mut bytes).clone_from_slice(b"xyz");
+        assert_eq!(bytes, *b"xyz");
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestr_copy_from_slice_length_mismatch() {
+        let mut bytes = [0; 3];
+        ByteStr::new_mut(&mut bytes).copy_from_slice(b"abcd");
+    }
+
+    #[test]
+    fn bytestr_fill() {
+        let mut bytes = *b"abcd";
+        ByteStr::new_mut(&mut bytes).fill(0x00);
+        assert_eq!(bytes, [0; 4]);
+
+        let mut next = 0;
+        ByteStr::new_mut(&mut bytes).fill_with(|| {
+            next += 1;
+            next
+        });
+        assert_eq!(bytes, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn bytestring_from_byte_and_char() {
+        assert_eq!(ByteString::from(b'a'), b"a"[..]);
+        assert_eq!(ByteString::from(0xffu8), b"\xff"[..]);
+        assert_eq!(ByteString::from('a'), b"a"[..]);
+        assert_eq!(ByteString::from('\u{e9}'), b"\xc3\xa9"[..]);
+        assert_eq!(ByteString::from('\u{1f600}'), b"\xf0\x9f\x98\x80"[..]);
+    }
+
+    #[test]
+    fn bytestr_partition() {
+        let (digits, others) = ByteStr::new(b"a1b22c3").partition(|b| b.is_ascii_digit());
+        assert_eq!(digits, b"1223"[..]);
+        assert_eq!(others, b"abc"[..]);
+    }
+
+    #[test]
+    fn bytestring_insert_byte() {
+        let mut bs = ByteString::new(b"bd".to_vec());
+        bs.insert_byte(0, b'a');
+        bs.insert_byte(2, b'c');
+        bs.insert_byte(4, b'e');
+        assert_eq!(bs, b"abcde"[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestring_insert_byte_out_of_bounds() {
+        let mut bs = ByteString::new(b"ab".to_vec());
+        bs.insert_byte(3, b'c');
+    }
+
+    #[test]
+    fn bytestr_escape_ascii() {
+        let bs = ByteStr::new(b"tab\t \"quote\" \\ \x00\xff");
+        let escaped: String = bs.escape_ascii().map(char::from).collect();
+        let debug = format!("{:?}", bs);
+        assert_eq!(escaped, &debug[2..debug.len() - 1]);
+    }
+
+    #[test]
+    fn bytestr_starts_ends_with_str() {
+        let bs = ByteStr::new(b"GET /caf\xc3\xa9");
+        assert!(bs.starts_with_str("GET "));
+        assert!(bs.ends_with_str("caf\u{e9}"));
+        assert!(ByteStr::new("\u{e9}t\u{e9}".as_bytes()).starts_with_str("\u{e9}"));
+        assert!(!bs.starts_with_str("POST"));
+        assert!(!bs.ends_with_str("cafe"));
+    }
+
+    #[test]
+    fn bytestring_dedup_byte() {
+        let mut bs = ByteString::new(b"aa  bb   aa".to_vec());
+        bs.dedup_byte(b' ');
+        assert_eq!(bs, b"aa bb aa"[..]);
+
+        let mut bs = ByteString::new(b"a b c".to_vec());
+        bs.dedup_byte(b' ');
+        assert_eq!(bs, b"a b c"[..]);
+    }
+
+    #[test]
+    fn bytestr_rchunks_exact() {
+        let chunks = ByteStr::new(b"abcdef").rchunks_exact(2);
+        assert_eq!(chunks.remainder(), ByteStr::new(b""));
+        assert_eq!(chunks.collect::<Vec<_>>(), [ByteStr::new(b"ef"), ByteStr::new(b"cd"), ByteStr::new(b"ab")]);
+
+        let chunks = ByteStr::new(b"abcde").rchunks_exact(2);
+        assert_eq!(chunks.remainder(), ByteStr::new(b"a"));
+        assert_eq!(chunks.collect::<Vec<_>>(), [ByteStr::new(b"de"), ByteStr::new(b"bc")]);
+    }
+
+    #[test]
+    fn bytestr_encode_hex_into() {
+        let bs = ByteStr::new(b"\x00\x7f\xab\xff");
+        let mut out = String::from("hex: ");
+        bs.encode_hex_into(&mut out);
+        assert_eq!(out, "hex: 007fabff");
+        out.push_str(", HEX: ");
+        bs.encode_upper_hex_into(&mut out);
+        assert_eq!(out, "hex: 007fabff, HEX: 007FABFF");
+    }
+
+    #[test]
+    fn bytestr_align_to_u32() {
+        let bytes: Vec<u8> = (0..37).collect();
+        let bs = ByteStr::new(&bytes[1..]);
+        let (head, words, tail) = bs.align_to_u32();
+        assert_eq!(head.len() + words.len() * 4 + tail.len(), bs.len());
+
+        let word_sum: u64 = words.iter().map(|&w| u64::from(w)).sum();
+        let expected: u64 = bs[head.len()..bs.len() - tail.len()]
+            .chunks(4)
+            .map(|chunk| u64::from(u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])))
+            .sum();
+        assert_eq!(word_sum, expected);
+    }
+
+    #[test]
+    fn bytestr_ascii_case() {
+        let bs = ByteStr::new(b"MiXeD 123 \xc9\xe9");
+        assert_eq!(bs.to_ascii_lowercase(), b"mixed 123 \xc9\xe9"[..]);
+        assert_eq!(bs.to_ascii_uppercase(), b"MIXED 123 \xc9\xe9"[..]);
+    }
+
+    #[test]
+    fn bytestring_pop_slice() {
+        let mut bs = ByteString::new(b"abcde".to_vec());
+        assert_eq!(bs.pop_slice(2), Some(b"de".to_vec()));
+        assert_eq!(bs, b"abc"[..]);
+        assert_eq!(bs.pop_slice(4), None);
+        assert_eq!(bs, b"abc"[..]);
+        assert_eq!(bs.pop_slice(3), Some(b"abc".to_vec()));
+        assert!(bs.is_empty());
+    }
+
+    #[test]
+    fn bytestr_truncate_escaped_to_width() {
+        let bs = ByteStr::new(b"ab\xffcd");
+        assert_eq!(bs.truncate_escaped_to_width(8), "ab\\xffcd");
+        assert_eq!(bs.truncate_escaped_to_width(7), "ab\\xff\u{2026}");
+        assert_eq!(bs.truncate_escaped_to_width(6), "ab\u{2026}");
+        assert_eq!(bs.truncate_escaped_to_width(4), "ab\u{2026}");
+        assert_eq!(bs.truncate_escaped_to_width(1), "\u{2026}");
+        assert_eq!(bs.truncate_escaped_to_width(0), "");
+    }
+
+    #[test]
+    fn bytestring_split_off() {
+        let mut bs = ByteString::new(b"abcd".to_vec());
+        assert_eq!(bs.split_off(4), b""[..]);
+        assert_eq!(bs.split_off(1), b"bcd"[..]);
+        assert_eq!(bs, b"a"[..]);
+        assert_eq!(bs.split_off(0), b"a"[..]);
+        assert!(bs.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestring_split_off_out_of_bounds() {
+        ByteString::new(b"abcd".to_vec()).split_off(5);
+    }
+
+    #[test]
+    fn bytestr_contains_slice() {
+        let bs = ByteStr::new(b"haystack");
+        assert!(bs.contains_slice(b"st"));
+        assert!(bs.contains_slice("hay"));
+        assert!(bs.contains_slice(ByteString::new(b"ack".to_vec())));
+        assert!(!bs.contains_slice(b"needle"));
+        assert!(bs.contains_slice(b""));
+        assert!(ByteStr::new(b"").contains_slice(b""));
+    }
+
+    #[test]
+    fn hex_bytestr_grouping() {
+        let bs = ByteStr::new(b"\xde\xad\xbe\xef\x01");
+        assert_eq!(format!("{:x}", bs), "deadbeef01");
+        assert_eq!(format!("{:.0x}", bs), "deadbeef01");
+        assert_eq!(format!("{:.1x}", bs), "de ad be ef 01");
+        assert_eq!(format!("{:.2x}", bs), "dead beef 01");
+        assert_eq!(format!("{:.2X}", bs), "DEAD BEEF 01");
+    }
+
+    #[test]
+    fn hex_bytestring() {
+        let bs = ByteString::new(b"\xca\xfe".to_vec());
+        assert_eq!(format!("{:x}", bs), "cafe");
+        assert_eq!(format!("{:.1X}", bs), "CA FE");
+        assert_eq!(format!("{:.1x}", ByteString::default()), "");
+    }
+
+    #[test]
+    fn bytestring_from_iter_capacity() {
+        let bytes = [b'a'; 100];
+        let bs: ByteString = bytes.iter().collect();
+        assert_eq!(bs, bytes[..]);
+        assert!(bs.capacity() >= 100);
+
+        let bs: ByteString = vec!['x'; 50].into_iter().collect();
+        assert_eq!(bs, [b'x'; 50][..]);
+        assert!(bs.capacity() >= 50);
+
+        let bs: ByteString = vec!["ab", "\u{e9}"].into_iter().collect();
+        assert_eq!(bs, "ab\u{e9}");
+
+        let bs: ByteString = vec!["abc"; 100].into_iter().collect();
+        assert_eq!(bs.len(), 300);
+        assert!(bs.capacity() >= 300);
+    }
+
+    #[test]
+    fn bytestr_rotate() {
+        let mut bytes = *b"abcd";
+        ByteStr::new_mut(&mut bytes).rotate_left(1);
+        assert_eq!(bytes, *b"bcda");
+
+        let mut bytes = *b"abcd";
+        ByteStr::new_mut(&mut bytes).rotate_right(1);
+        assert_eq!(bytes, *b"dabc");
+    }
+
+    #[test]
+    fn bytestr_distinct_sorted() {
+        assert_eq!(ByteStr::new(b"hello world\xff\x00").distinct_sorted(), b"\x00 dehlorw\xff"[..]);
+        assert_eq!(ByteStr::new(b"").distinct_sorted(), b""[..]);
+    }
+
+    #[test]
+    fn bytestr_from_bytestring_ref() {
+        fn len_of<'a, T: Into<&'a ByteStr>>(s: T) -> usize {
+            s.into().len()
+        }
+
+        let bs = ByteString::new(b"abc".to_vec());
+        assert_eq!(len_of(&bs), 3);
+        let borrowed: &ByteStr = (&bs).into();
+        assert_eq!(borrowed, ByteStr::new(b"abc"));
+    }
+
+    #[test]
+    fn bytestr_hex_chars() {
+        let bs = ByteStr::new(b"\x01\xab\xff");
+        let mut expected = String::new();
+        bs.encode_hex_into(&mut expected);
+        assert_eq!(bs.hex_chars().collect::<String>(), expected);
+        assert_eq!(bs.hex_chars().len(), 6);
+
+        let mut expected = String::new();
+        bs.encode_upper_hex_into(&mut expected);
+        assert_eq!(bs.upper_hex_chars().collect::<String>(), expected);
+    }
+
+    #[test]
+    fn bytestring_insert_str() {
+        let mut bs = ByteString::new(b"bd".to_vec());
+        bs.insert_str(0, "a");
+        bs.insert_str(2, "\u{e9}");
+        bs.insert_str(bs.len(), "end");
+        assert_eq!(bs, "ab\u{e9}dend");
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestring_insert_str_out_of_bounds() {
+        let mut bs = ByteString::new(b"ab".to_vec());
+        bs.insert_str(3, "c");
+    }
+
+    #[test]
+    fn bytestr_equal_under_mask() {
+        let bs = ByteStr::new(b"\x12\x34\x56");
+        assert!(bs.equal_under_mask(b"\x12\xff\x56", b"\xff\x00\xff"));
+        assert!(bs.equal_under_mask(b"\x1f\x34\x56", b"\xf0\xff\xff"));
+        assert!(!bs.equal_under_mask(b"\x12\x35\x56", b"\xff\xff\xff"));
+        assert!(!bs.equal_under_mask(b"\x12\x34", b"\xff\xff"));
+    }
+
+    #[test]
+    fn bytestring_repeat_in_place() {
+        let mut bs = ByteString::new(b"ab".to_vec());
+        bs.repeat_in_place(1);
+        assert_eq!(bs, b"ab"[..]);
+        bs.repeat_in_place(3);
+        assert_eq!(bs, b"ababab"[..]);
+        bs.repeat_in_place(0);
+        assert!(bs.is_empty());
+    }
+
+    #[test]
+    fn bytestr_byte_at() {
+        let bs = ByteStr::new(b"abc");
+        assert_eq!(bs.byte_at(0), Some(b'a'));
+        assert_eq!(bs.byte_at(2), Some(b'c'));
+        assert_eq!(bs.byte_at(3), None);
+    }
+
+    #[test]
+    fn bytestr_to_utf8_lossy_bytes() {
+        let valid = ByteStr::new("caf\u{e9}".as_bytes());
+        assert_eq!(valid.to_utf8_lossy_bytes(), valid.0[..]);
+        assert_eq!(ByteStr::new(b"a\xffb").to_utf8_lossy_bytes(), b"a\xef\xbf\xbdb"[..]);
+    }
+
+    #[test]
+    fn cmp_cow() {
+        let borrowed: Cow<[u8]> = Cow::Borrowed(b"abc");
+        let owned: Cow<[u8]> = Cow::Owned(b"abd".to_vec());
+
+        let bs = ByteStr::new(b"abc");
+        assert!(*bs == borrowed);
+        assert!(*bs != owned);
+        assert!(*bs < owned);
+
+        let bs = ByteString::new(b"abd".to_vec());
+        assert!(bs == owned);
+        assert!(bs != borrowed);
+        assert!(bs > borrowed);
+    }
+
+    #[test]
+    fn bytestr_count_leading_trailing() {
+        let bs = ByteStr::new(b"\0\0data  ");
+        assert_eq!(bs.count_leading(0), 2);
+        assert_eq!(bs.count_trailing(b' '), 2);
+        assert_eq!(bs.count_leading(b' '), 0);
+
+        let bs = ByteStr::new(b"xxx");
+        assert_eq!(bs.count_leading(b'x'), 3);
+        assert_eq!(bs.count_trailing(b'x'), 3);
+    }
+
+    #[test]
+    fn bytestring_swap_remove_byte() {
+        let mut bs = ByteString::new(b"abcd".to_vec());
+        assert_eq!(bs.swap_remove_byte(1), b'b');
+        assert_eq!(bs, b"adc"[..]);
+        assert_eq!(bs.swap_remove_byte(2), b'c');
+        assert_eq!(bs, b"ad"[..]);
+    }
+
+    #[test]
+    fn bytestr_split_before_after_byte() {
+        let bs = ByteStr::new(b"ab\ncd");
+        assert_eq!(bs.split_before_byte(b'\n'), Some((ByteStr::new(b"ab"), ByteStr::new(b"\ncd"))));
+        assert_eq!(bs.split_after_byte(b'\n'), Some((ByteStr::new(b"ab\n"), ByteStr::new(b"cd"))));
+        assert_eq!(bs.split_before_byte(b'x'), None);
+        assert_eq!(bs.split_after_byte(b'x'), None);
+    }
+
+    #[test]
+    fn bytestr_parse_u64() {
+        use std::num::IntErrorKind;
+
+        assert_eq!(ByteStr::new(b"12345").parse_u64(10), Ok(12345));
+        assert_eq!(ByteStr::new(b"ff").parse_u64(16), Ok(255));
+        assert_eq!(ByteStr::new(b"12x").parse_u64(10).unwrap_err().kind(), &IntErrorKind::InvalidDigit);
+        assert_eq!(ByteStr::new(b"1\xff").parse_u64(10).unwrap_err().kind(), &IntErrorKind::InvalidDigit);
+        assert_eq!(ByteStr::new(b"").parse_u64(10).unwrap_err().kind(), &IntErrorKind::Empty);
+        assert_eq!(ByteStr::new(b"+5").parse_u64(10).unwrap_err().kind(), &IntErrorKind::InvalidDigit);
+        assert_eq!(ByteStr::new(b"+").parse_u64(10).unwrap_err().kind(), &IntErrorKind::InvalidDigit);
+        assert_eq!(
+            ByteStr::new(b"18446744073709551616").parse_u64(10).unwrap_err().kind(),
+            &IntErrorKind::PosOverflow
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn bytestring_bytes_conversions() {
+        let b = bytes::Bytes::from(ByteString::new(b"network".to_vec()));
+        assert_eq!(&b[..], b"network");
+
+        let bs = ByteString::from(b.clone());
+        assert_eq!(bs, b"network"[..]);
+
+        let ptr = bs.as_ptr();
+        let mut b = bytes::BytesMut::from(bs);
+        assert_eq!(b.as_ptr(), ptr);
+        b.extend_from_slice(b" buffer");
+        assert_eq!(&b[..], b"network buffer");
+    }
+
+    #[test]
+    fn bytestr_chunks_mut() {
+        let mut bytes = [0; 7];
+        for (i, chunk) in ByteStr::new_mut(&mut bytes).chunks_mut(3).enumerate() {
+            chunk.fill(i as u8);
+        }
+        assert_eq!(bytes, [0, 0, 0, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn bytestring_clone_from() {
+        let mut bs = ByteString::new(Vec::with_capacity(64));
+        bs.extend(b"previous contents");
+        let ptr = bs.as_ptr();
+        let capacity = bs.capacity();
+
+        bs.clone_from(&ByteString::new(b"short".to_vec()));
+        assert_eq!(bs, b"short"[..]);
+        assert_eq!(bs.as_ptr(), ptr);
+        assert_eq!(bs.capacity(), capacity);
+    }
+
+    #[test]
+    fn bytestr_first_last_byte() {
+        let bs = ByteStr::new(b"abc");
+        assert_eq!(bs.first_byte(), Some(b'a'));
+        assert_eq!(bs.last_byte(), Some(b'c'));
+        assert_eq!(ByteStr::new(b"").first_byte(), None);
+        assert_eq!(ByteStr::new(b"").last_byte(), None);
+    }
+
+    #[test]
+    fn bytestring_pad() {
+        let mut bs = ByteString::new(b"42".to_vec());
+        bs.pad_start_to(5, b'0');
+        assert_eq!(bs, b"00042"[..]);
+        bs.pad_to(8, 0);
+        assert_eq!(bs, b"00042\0\0\0"[..]);
+        bs.pad_to(4, 0);
+        bs.pad_start_to(4, 0);
+        assert_eq!(bs, b"00042\0\0\0"[..]);
+    }
+
+    #[test]
+    fn bytestr_ct_eq() {
+        let bs = ByteStr::new(b"secret mac");
+        assert!(bs.ct_eq(b"secret mac"));
+        assert!(!bs.ct_eq(b"secret mad"));
+        assert!(!bs.ct_eq(b"Secret mac"));
+        assert!(!bs.ct_eq(b"secret"));
+        assert!(ByteStr::new(b"").ct_eq(b""));
+    }
+
+    #[test]
+    fn bytestring_retain_range() {
+        let mut bs = ByteString::new(b"abcdef".to_vec());
+        bs.retain_range(1..5);
+        assert_eq!(bs, b"bcde"[..]);
+        bs.retain_range(0..2);
+        assert_eq!(bs, b"bc"[..]);
+        bs.retain_range(1..2);
+        assert_eq!(bs, b"c"[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestring_retain_range_out_of_bounds() {
+        let mut bs = ByteString::new(b"abc".to_vec());
+        bs.retain_range(1..4);
+    }
+
+    #[test]
+    fn bytestr_map_bytes() {
+        assert_eq!(ByteStr::new(b"HAL").map_bytes(|b| b + 1), b"IBM"[..]);
+        assert_eq!(ByteStr::new(b"MiXeD\xc9").map_bytes(|b| b.to_ascii_lowercase()), b"mixed\xc9"[..]);
+    }
+
+    #[test]
+    fn bytestring_shift() {
+        let mut bs = ByteString::new(b"abcd".to_vec());
+        bs.shift_left(1);
+        assert_eq!(bs, b"bcd"[..]);
+        bs.shift_right(2, b'-');
+        assert_eq!(bs, b"--bcd"[..]);
+        bs.shift_left(10);
+        assert!(bs.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rkyv")]
+    fn bytestring_rkyv_roundtrip() {
+        let bs = ByteString::new(b"archived\x00bytes".to_vec());
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&bs).unwrap();
+
+        let archived =
+            rkyv::access::<rkyv::vec::ArchivedVec<u8>, rkyv::rancor::Error>(&bytes).unwrap();
+        assert_eq!(archived.as_slice(), b"archived\x00bytes");
+
+        let deserialized = rkyv::deserialize::<ByteString, rkyv::rancor::Error>(archived).unwrap();
+        assert_eq!(deserialized, bs);
+    }
+
+    #[test]
+    fn bytestr_trim_trailing_nuls_and_as_cstr() {
+        let buf = ByteStr::new(b"name\0\0\0\0");
+        assert_eq!(buf.trim_trailing_nuls(), ByteStr::new(b"name"));
+        assert_eq!(buf.as_cstr(), ByteStr::new(b"name"));
+
+        let buf = ByteStr::new(b"abc\0def\0\0");
+        assert_eq!(buf.trim_trailing_nuls(), ByteStr::new(b"abc\0def"));
+        assert_eq!(buf.as_cstr(), ByteStr::new(b"abc"));
+
+        assert_eq!(ByteStr::new(b"no nul").as_cstr(), ByteStr::new(b"no nul"));
+    }
+
+    #[test]
+    fn bytestring_read_line() {
+        let mut r = io::Cursor::new(b"first\nsecond\n");
+        assert_eq!(ByteString::read_line(&mut r).unwrap(), Some(ByteString::new(b"first\n".to_vec())));
+        assert_eq!(ByteString::read_line(&mut r).unwrap(), Some(ByteString::new(b"second\n".to_vec())));
+        assert_eq!(ByteString::read_line(&mut r).unwrap(), None);
+
+        let mut r = io::Cursor::new(b"line\nlast");
+        assert_eq!(ByteString::read_line(&mut r).unwrap(), Some(ByteString::new(b"line\n".to_vec())));
+        assert_eq!(ByteString::read_line(&mut r).unwrap(), Some(ByteString::new(b"last".to_vec())));
+        assert_eq!(ByteString::read_line(&mut r).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "crc")]
+    fn bytestr_crc32() {
+        assert_eq!(ByteStr::new(b"").crc32(), 0);
+        assert_eq!(ByteStr::new(b"123456789").crc32(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn bytestring_collapse_ascii_whitespace() {
+        let mut bs = ByteString::new(b"  one\t\ttwo   three\n\r\nfour \n".to_vec());
+        bs.collapse_ascii_whitespace();
+        assert_eq!(bs, b"one two three four"[..]);
+
+        let mut bs = ByteString::new(b" \t\n ".to_vec());
+        bs.collapse_ascii_whitespace();
+        assert!(bs.is_empty());
+    }
+
+    #[test]
+    fn bytestr_split_terminator() {
+        let expected = vec![ByteStr::new(b"a"), ByteStr::new(b"b")];
+        assert_eq!(ByteStr::new(b"a\nb\n").split_terminator(b'\n').collect::<Vec<_>>(), expected);
+        assert_eq!(ByteStr::new(b"a\nb").split_terminator(b'\n').collect::<Vec<_>>(), expected);
+
+        let segments: Vec<_> = ByteStr::new(b"a\n\nb\n\n").split_terminator(b'\n').collect();
+        assert_eq!(segments, vec![ByteStr::new(b"a"), ByteStr::new(b""), ByteStr::new(b"b"), ByteStr::new(b"")]);
+        assert_eq!(ByteStr::new(b"").split_terminator(b'\n').count(), 0);
+    }
+
+    #[test]
+    fn bytestring_try_reserve() {
+        let mut bs = ByteString::new(b"abc".to_vec());
+        assert!(bs.try_reserve(16).is_ok());
+        assert!(bs.capacity() >= 19);
+
+        let mut bs = ByteString::new(b"abc".to_vec());
+        assert!(bs.try_reserve_exact(16).is_ok());
+        assert!(bs.capacity() >= 19);
+        assert_eq!(bs, b"abc"[..]);
+    }
+
+    #[test]
+    fn bytestr_debug_wrapped() {
+        let bs = ByteStr::new(b"abcdef\x00gh\n\"ij");
+        assert_eq!(format!("{:?}", bs.debug_wrapped(4)), "b\"abcd\nef\n\\x00\ngh\\n\n\\\"ij\"");
+        assert_eq!(format!("{:?}", bs.debug_wrapped(100)), format!("{:?}", bs));
+
+        let bs = ByteStr::new(b"\xff\x01tail text\t\\");
+        let wrapped = format!("{:?}", bs.debug_wrapped(6));
+        let body = &wrapped[2..wrapped.len() - 1];
+        assert!(body.lines().all(|line| !line.is_empty() && line.len() <= 6));
+        assert!(body.lines().all(|line| !line.ends_with('\\') || line.ends_with("\\\\")));
+        assert_eq!(body.replace('\n', ""), format!("{:?}", bs)[2..].trim_end_matches('"'));
+    }
+
+    #[test]
+    fn bytestr_into_owned_chunks() {
+        let chunks = ByteStr::new(b"abcdef").into_owned_chunks(2);
+        assert_eq!(chunks, vec![ByteString::new(b"ab".to_vec()),
+                                ByteString::new(b"cd".to_vec()),
+                                ByteString::new(b"ef".to_vec())]);
+
+        let chunks = ByteStr::new(b"abcdefg").into_owned_chunks(3);
+        assert_eq!(chunks, vec![ByteString::new(b"abc".to_vec()),
+                                ByteString::new(b"def".to_vec()),
+                                ByteString::new(b"g".to_vec())]);
+        assert!(ByteStr::new(b"").into_owned_chunks(3).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bytestr_into_owned_chunks_zero_size() {
+        ByteStr::new(b"abc").into_owned_chunks(0);
     }
 }